@@ -1,31 +1,41 @@
 extern crate criterion;
 
-use bee2_bash::{bash_f0, Bash256, Bash384, Bash512};
-use bee2_bash::{
-    BashPrgAEAD2561, BashPrgAEAD2562, BashPrgAEAD3841, BashPrgAEAD3842, BashPrgAEAD5121,
-    BashPrgAEAD5122,
-};
 use bee2_bash::{
+    bash_f0, Bash256, Bash384, Bash512, BashFBackend, BashPrgAEAD2561, BashPrgAEAD5122,
     BashPrgHash2561, BashPrgHash2562, BashPrgHash3841, BashPrgHash3842, BashPrgHash5121,
     BashPrgHash5122,
 };
 use bee2_traits::{Hasher, PrgAEAD, PrgHasher};
-use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use rand::{thread_rng, Rng};
 
 fn bash_256(bytes: &[u8]) {
     let mut basher = Bash256::new();
-    basher.step_h(&bytes);
+    basher.step_h(bytes);
 }
 
 fn bash_384(bytes: &[u8]) {
     let mut basher = Bash384::new();
-    basher.step_h(&bytes);
+    basher.step_h(bytes);
 }
 
 fn bash_512(bytes: &[u8]) {
     let mut basher = Bash512::new();
-    basher.step_h(&bytes);
+    basher.step_h(bytes);
+}
+
+fn prg_hash<H: PrgHasher>(bytes: &[u8]) {
+    let mut hash = [0u8; 64];
+    H::new([]).unwrap().hash(bytes, &mut hash);
+}
+
+fn prg_aead<A: PrgAEAD>(bytes: &[u8]) {
+    let mut ciphertext = [0u8; 1024];
+    let mut tag = [0u8; 32];
+    A::new([], [0x42; 32])
+        .unwrap()
+        .encrypt(bytes, [], &mut ciphertext, &mut tag)
+        .unwrap();
 }
 
 fn bashf_test(bytes: &mut [u64; 24]) {
@@ -49,17 +59,65 @@ fn bench_bash(c: &mut Criterion) {
     let mut group = c.benchmark_group("primitiveBench");
     group.throughput(Throughput::Bytes((block.len() * 8) as u64));
     group.bench_function("f0", |b| b.iter(|| bashf_test(&mut block)));
+    for backend in [
+        BashFBackend::Portable,
+        BashFBackend::Sse2,
+        BashFBackend::Avx2,
+    ] {
+        if backend.is_supported() {
+            let name = format!("f0-{:?}", backend);
+            group.bench_function(name, |b| b.iter(|| backend.bash_f0(&mut block)));
+        }
+    }
     group.finish();
 
-    // let mut group = c.benchmark_group("BasPrghBench");
-    // group.throughput(Throughput::Bytes(bytes.len() as u64));
-    // group.bench_function("bash-prg-hash2561", |b| b.iter(|| bash_256(&bytes)));
-    // group.bench_function("bash-prg-hash2561", |b| b.iter(|| bash_384(&bytes)));
-    // group.bench_function("bash-prg-hash2561", |b| b.iter(|| bash_256(&bytes)));
-    // group.bench_function("bash-prg-hash2561", |b| b.iter(|| bash_384(&bytes)));
-    // group.bench_function("bash-prg-hash2561", |b| b.iter(|| bash_256(&bytes)));
-    // group.bench_function("bash-prg-hash2561", |b| b.iter(|| bash_384(&bytes)));
-    // group.finish();
+    let mut records = vec![[0u8; 100]; 256];
+    records
+        .iter_mut()
+        .for_each(|r| thread_rng().fill(&mut r[..]));
+    let mut hashes = vec![[0u8; 32]; records.len()];
+
+    let mut group = c.benchmark_group("multiBench");
+    group.throughput(Throughput::Bytes((records.len() * 100) as u64));
+    group.bench_function("bash256-sequential", |b| {
+        b.iter(|| {
+            for (hash, record) in hashes.iter_mut().zip(records.iter()) {
+                Bash256::hash(hash, record);
+            }
+        })
+    });
+    group.bench_function("bash256-many", |b| {
+        b.iter(|| Bash256::hash_many(&mut hashes, &records))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("BashPrgBench");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("bash-prg-hash2561", |b| {
+        b.iter(|| prg_hash::<BashPrgHash2561>(&bytes))
+    });
+    group.bench_function("bash-prg-hash2562", |b| {
+        b.iter(|| prg_hash::<BashPrgHash2562>(&bytes))
+    });
+    group.bench_function("bash-prg-hash3841", |b| {
+        b.iter(|| prg_hash::<BashPrgHash3841>(&bytes))
+    });
+    group.bench_function("bash-prg-hash3842", |b| {
+        b.iter(|| prg_hash::<BashPrgHash3842>(&bytes))
+    });
+    group.bench_function("bash-prg-hash5121", |b| {
+        b.iter(|| prg_hash::<BashPrgHash5121>(&bytes))
+    });
+    group.bench_function("bash-prg-hash5122", |b| {
+        b.iter(|| prg_hash::<BashPrgHash5122>(&bytes))
+    });
+    group.bench_function("bash-prg-aead2561", |b| {
+        b.iter(|| prg_aead::<BashPrgAEAD2561>(&bytes))
+    });
+    group.bench_function("bash-prg-aead5122", |b| {
+        b.iter(|| prg_aead::<BashPrgAEAD5122>(&bytes))
+    });
+    group.finish();
 }
 
 criterion_group!(benches, bench_bash);
@@ -0,0 +1,170 @@
+//! Export and import of internal states.
+//!
+//! A partially processed message can be hashed further in another process:
+//! `export_state()` of `Bash256/384/512`, `BashPrgHash*` and `BashPrg` gives
+//! `STATE_LEN` octets and `import_state()` of the same type restores the object.
+//!
+//! # Format, version 1
+//!
+//! ```text
+//! state = "BSS" || 0x01 || kind || <l/8>_8 || <d>_8 || flags || <pos>_16 || 0^6 || s || check
+//! check = bash256(all previous octets)
+//! ```
+//!
+//! `kind` is 0x01 for `bash-hash`, 0x02 for `bash-prg` and 0x03 for
+//! `bash-prg-hash`, `d` is 0 for `bash-hash`. Bit 0 of `flags` is set for an
+//! automaton in key mode, bit 1 if `absorb` of `bash-prg-hash` is started.
+//! `pos` is little-endian, `s` is 192 octets of the state.
+//!
+//! The check value detects corruption, it does not authenticate the state.
+//! The state of a keyed automaton is as secret as its key.
+
+use bee2_core::error::StateError;
+
+use crate::consts::State;
+use crate::hash::{Bash256, Hasher};
+
+/// Format identifier and version.
+const MAGIC: [u8; 3] = *b"BSS";
+const VERSION: u8 = 1;
+/// Length of fields before the state.
+const HEADER_LEN: usize = 16;
+/// Length of check value.
+const CHECK_LEN: usize = 32;
+/// Length of exported state.
+pub const STATE_LEN: usize = HEADER_LEN + 192 + CHECK_LEN;
+
+pub(crate) const KIND_HASH: u8 = 0x01;
+pub(crate) const KIND_PRG: u8 = 0x02;
+pub(crate) const KIND_PRG_HASH: u8 = 0x03;
+
+pub(crate) const FLAG_KEY: u8 = 0x01;
+pub(crate) const FLAG_ABSORBING: u8 = 0x02;
+
+/// Fields of exported state.
+pub(crate) struct Header {
+    pub(crate) kind: u8,
+    pub(crate) l: usize,
+    pub(crate) d: usize,
+    pub(crate) flags: u8,
+    pub(crate) pos: usize,
+}
+
+fn check(data: &[u8]) -> [u8; CHECK_LEN] {
+    let mut check = [0u8; CHECK_LEN];
+    Bash256::hash(&mut check, data);
+    check
+}
+
+/// Export `header` and `s`.
+pub(crate) fn encode(header: &Header, s: &State) -> [u8; STATE_LEN] {
+    let mut out = [0u8; STATE_LEN];
+    out[..3].copy_from_slice(&MAGIC);
+    out[3] = VERSION;
+    out[4] = header.kind;
+    out[5] = (header.l / 8) as u8;
+    out[6] = header.d as u8;
+    out[7] = header.flags;
+    out[8..10].copy_from_slice(&(header.pos as u16).to_le_bytes());
+    out[HEADER_LEN..HEADER_LEN + 192].copy_from_slice(&s[..]);
+    let check = check(&out[..STATE_LEN - CHECK_LEN]);
+    out[STATE_LEN - CHECK_LEN..].copy_from_slice(&check);
+    out
+}
+
+/// Import exported state of `kind`.
+///
+/// Parameters and `pos` are checked by the caller.
+pub(crate) fn decode(state: &[u8], kind: u8) -> Result<(Header, State), StateError> {
+    if state.len() != STATE_LEN || state[..3] != MAGIC {
+        return Err(StateError::InvalidFormat);
+    }
+    if state[3] != VERSION {
+        return Err(StateError::UnsupportedVersion(state[3]));
+    }
+    if check(&state[..STATE_LEN - CHECK_LEN])[..] != state[STATE_LEN - CHECK_LEN..] {
+        return Err(StateError::ChecksumMismatch);
+    }
+    if state[10..HEADER_LEN].iter().any(|&x| x != 0) || state[7] & !(FLAG_KEY | FLAG_ABSORBING) != 0
+    {
+        return Err(StateError::InvalidFormat);
+    }
+    if state[4] != kind {
+        return Err(StateError::TypeMismatch);
+    }
+
+    let mut s = State::default();
+    s.copy_from_slice(&state[HEADER_LEN..HEADER_LEN + 192]);
+    let header = Header {
+        kind,
+        l: state[5] as usize * 8,
+        d: state[6] as usize,
+        flags: state[7],
+        pos: u16::from_le_bytes([state[8], state[9]]) as usize,
+    };
+    Ok((header, s))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Bash384;
+
+    #[test]
+    fn checkpoint_test_format() {
+        let mut hasher = Bash384::new();
+        hasher.step_h([1u8; 100]);
+        let state = hasher.export_state();
+        assert_eq!(state.len(), STATE_LEN);
+        assert!(Bash384::import_state(state).is_ok());
+
+        assert_eq!(
+            Bash384::import_state(&state[..STATE_LEN - 1]).err(),
+            Some(StateError::InvalidFormat)
+        );
+        let mut other = state;
+        other[0] ^= 1;
+        assert_eq!(
+            Bash384::import_state(other).err(),
+            Some(StateError::InvalidFormat)
+        );
+        let mut other = state;
+        other[3] = 2;
+        assert_eq!(
+            Bash384::import_state(other).err(),
+            Some(StateError::UnsupportedVersion(2))
+        );
+        for i in [4, 8, 16, 100, STATE_LEN - 1].iter() {
+            let mut other = state;
+            other[*i] ^= 0x80;
+            assert_eq!(
+                Bash384::import_state(other).err(),
+                Some(StateError::ChecksumMismatch)
+            );
+        }
+
+        // Fields are checked after the check value.
+        let header = Header {
+            kind: KIND_HASH,
+            l: 192,
+            d: 0,
+            flags: 0,
+            pos: 96,
+        };
+        let other = encode(&header, &State::default());
+        assert_eq!(
+            Bash384::import_state(other).err(),
+            Some(StateError::InvalidFormat)
+        );
+        let header = Header { pos: 95, ..header };
+        assert!(Bash384::import_state(encode(&header, &State::default())).is_ok());
+        let header = Header {
+            flags: 0x04,
+            ..header
+        };
+        assert_eq!(
+            Bash384::import_state(encode(&header, &State::default())).err(),
+            Some(StateError::InvalidFormat)
+        );
+    }
+}
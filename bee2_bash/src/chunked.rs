@@ -0,0 +1,442 @@
+//! Chunked authenticated encryption of streams by `BashPrg`.
+//!
+//! Data is split into chunks, every chunk is encrypted and authenticated
+//! separately, so a stream is processed in constant memory and reordering,
+//! truncation or extension of chunks is detected.
+//!
+//! # Format, version 1
+//!
+//! ```text
+//! stream = header || chunk_0 || ... || chunk_{n-1}
+//! header = "BPS" || 0x01 || <l/8>_8 || <d>_8 || 0x00 0x00 || <chunk_len>_32 || nonce
+//! chunk  = ciphertext || tag
+//! ```
+//!
+//! Numbers are little-endian, `nonce` is 16 octets, `tag` is `l/8` octets.
+//! All chunks except the last one have `chunk_len` octets of ciphertext,
+//! the last one has from 1 to `chunk_len` octets, or 0 if the stream is empty.
+//!
+//! The automaton is started as `BashPrg::start(l, d, nonce, key)` and loads
+//! the first 12 octets of `header` by `absorb`. Then for every chunk:
+//!
+//! 1. `absorb(flag)`, `flag` is one octet: 0x01 for the last chunk and 0x00 otherwise;
+//! 2. `encr(chunk)`;
+//! 3. `squeeze(tag)`;
+//! 4. `ratchet()`.
+
+use std::io::{Error as IoError, ErrorKind, Read, Result, Write};
+
+use bee2_core::mem::mem_eq;
+
+use crate::prg::{
+    BashPrg, Error, IncorrectTag, InvalidLength, PrgAbsorb, PrgDecr, PrgEncr, PrgRatchet,
+    PrgSqueeze, PrgStart,
+};
+
+/// Format identifier and version.
+const MAGIC: [u8; 4] = *b"BPS\x01";
+/// Length of the authenticated part of header.
+const PARAMS_LEN: usize = 12;
+/// Length of nonce.
+pub const NONCE_LEN: usize = 16;
+/// Length of header.
+pub const HEADER_LEN: usize = PARAMS_LEN + NONCE_LEN;
+/// Default length of chunk.
+pub const CHUNK_LEN: usize = 1 << 16;
+/// Maximal length of chunk.
+pub const MAX_CHUNK_LEN: usize = 1 << 24;
+
+const FLAG_NEXT: u8 = 0x00;
+const FLAG_LAST: u8 = 0x01;
+
+fn invalid_input(err: impl Into<Error>) -> IoError {
+    IoError::new(ErrorKind::InvalidInput, err.into())
+}
+
+fn invalid_data(err: impl Into<Error>) -> IoError {
+    IoError::new(ErrorKind::InvalidData, err.into())
+}
+
+/// Authenticated part of header.
+fn params(l: usize, d: usize, chunk_len: usize) -> [u8; PARAMS_LEN] {
+    let mut params = [0u8; PARAMS_LEN];
+    params[..4].copy_from_slice(&MAGIC);
+    params[4] = (l / 8) as u8;
+    params[5] = d as u8;
+    params[8..].copy_from_slice(&(chunk_len as u32).to_le_bytes());
+    params
+}
+
+/// Start automaton and load header.
+fn start(
+    l: usize,
+    d: usize,
+    key: &[u8],
+    chunk_len: usize,
+    nonce: &[u8],
+) -> core::result::Result<BashPrg, InvalidLength> {
+    if key.is_empty() || chunk_len == 0 || chunk_len > MAX_CHUNK_LEN {
+        return Err(InvalidLength);
+    }
+    let mut prg = BashPrg::start(l, d, nonce, key)?;
+    prg.absorb(params(l, d, chunk_len));
+    Ok(prg)
+}
+
+/// Encoder of the chunked format.
+///
+/// Data is buffered up to a chunk, `finish()` must be called to write the last chunk.
+pub struct BashPrgStreamEncoder<W: Write> {
+    prg: BashPrg,
+    inner: W,
+    /// Plaintext of the current chunk, then ciphertext and tag.
+    buf: Vec<u8>,
+    chunk_len: usize,
+    tag_len: usize,
+}
+
+impl<W: Write> BashPrgStreamEncoder<W> {
+    /// Start encoding and write header.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l == 128 || l == 192 || l == 256
+    /// * d - capacity, d == 1 || d == 2
+    /// * key - key, key.len() % 4 == 0 && key.len() <= 60 && key.len() >= l / 8
+    /// * nonce - unique for every stream encrypted with `key`.
+    /// * chunk_len - length of chunk, 0 < chunk_len <= MAX_CHUNK_LEN.
+    /// * inner - destination of stream.
+    pub fn new(
+        l: usize,
+        d: usize,
+        key: impl AsRef<[u8]>,
+        nonce: &[u8; NONCE_LEN],
+        chunk_len: usize,
+        mut inner: W,
+    ) -> Result<Self> {
+        let prg = start(l, d, key.as_ref(), chunk_len, nonce).map_err(invalid_input)?;
+        inner.write_all(&params(l, d, chunk_len))?;
+        inner.write_all(nonce)?;
+        Ok(Self {
+            prg,
+            inner,
+            buf: Vec::with_capacity(chunk_len + l / 8),
+            chunk_len,
+            tag_len: l / 8,
+        })
+    }
+
+    fn seal_chunk(&mut self, flag: u8) -> Result<()> {
+        let len = self.buf.len();
+        self.prg.absorb([flag]);
+        self.prg.encr(&mut self.buf).map_err(invalid_input)?;
+        self.buf.resize(len + self.tag_len, 0);
+        self.prg.squeeze(&mut self.buf[len..]);
+        self.prg.ratchet();
+        self.inner.write_all(&self.buf)?;
+        self.buf.clear();
+
+        Ok(())
+    }
+
+    /// Write the last chunk.
+    ///
+    /// Returns `inner`.
+    pub fn finish(mut self) -> Result<W> {
+        self.seal_chunk(FLAG_LAST)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for BashPrgStreamEncoder<W> {
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        // The full chunk is not the last one: more data follows.
+        if self.buf.len() == self.chunk_len {
+            self.seal_chunk(FLAG_NEXT)?;
+        }
+        let count = data.len().min(self.chunk_len - self.buf.len());
+        self.buf.extend_from_slice(&data[..count]);
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decoder of the chunked format.
+///
+/// Plaintext of a chunk is returned only after its tag is verified.
+/// The end of data is returned only after the last chunk, a truncated
+/// or damaged stream gives an error of kind `ErrorKind::InvalidData`.
+pub struct BashPrgStreamDecoder<R: Read> {
+    prg: BashPrg,
+    inner: R,
+    /// Current chunk and one octet of the next one.
+    buf: Vec<u8>,
+    chunk_len: usize,
+    tag_len: usize,
+    /// Verified plaintext is `buf[pos..len]`.
+    pos: usize,
+    len: usize,
+    /// First octet of the next chunk.
+    next: Option<u8>,
+    finished: bool,
+    failed: bool,
+}
+
+impl<R: Read> BashPrgStreamDecoder<R> {
+    /// Start decoding and read header.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level of the stream.
+    /// * d - capacity of the stream.
+    /// * key - key of the stream.
+    /// * inner - source of stream.
+    pub fn new(l: usize, d: usize, key: impl AsRef<[u8]>, mut inner: R) -> Result<Self> {
+        let mut header = [0u8; HEADER_LEN];
+        inner.read_exact(&mut header)?;
+        let mut chunk_len = [0u8; 4];
+        chunk_len.copy_from_slice(&header[8..PARAMS_LEN]);
+        let chunk_len = u32::from_le_bytes(chunk_len) as usize;
+        if header[..PARAMS_LEN] != params(l, d, chunk_len) {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                "unknown format or parameters of stream",
+            ));
+        }
+        let prg =
+            start(l, d, key.as_ref(), chunk_len, &header[PARAMS_LEN..]).map_err(invalid_data)?;
+        Ok(Self {
+            prg,
+            inner,
+            buf: Vec::with_capacity(chunk_len + l / 8 + 1),
+            chunk_len,
+            tag_len: l / 8,
+            pos: 0,
+            len: 0,
+            next: None,
+            finished: false,
+            failed: false,
+        })
+    }
+
+    /// Get `inner`, the rest of stream is not checked.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read and verify the next chunk.
+    fn open_chunk(&mut self) -> Result<()> {
+        let full = self.chunk_len + self.tag_len;
+        self.buf.clear();
+        self.buf.extend(self.next.take());
+        let mut filled = self.buf.len();
+        self.buf.resize(full + 1, 0);
+        while filled <= full {
+            match self.inner.read(&mut self.buf[filled..]) {
+                Ok(0) => break,
+                Ok(count) => filled += count,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        // The chunk is the last one if nothing follows it.
+        let flag = if filled > full {
+            self.next = Some(self.buf[full]);
+            filled = full;
+            FLAG_NEXT
+        } else {
+            FLAG_LAST
+        };
+        if filled < self.tag_len {
+            return Err(invalid_data(IncorrectTag));
+        }
+        let len = filled - self.tag_len;
+        let (text, tag) = self.buf.split_at_mut(len);
+
+        self.prg.absorb([flag]);
+        self.prg.decr(text).map_err(invalid_data)?;
+        let mut tag_get = [0u8; 32];
+        let tag_get = &mut tag_get[..self.tag_len];
+        self.prg.squeeze(tag_get);
+        if !mem_eq(tag_get, &tag[..self.tag_len]) {
+            text.iter_mut().for_each(|x| *x = 0);
+            return Err(invalid_data(IncorrectTag));
+        }
+        self.prg.ratchet();
+
+        self.pos = 0;
+        self.len = len;
+        self.finished = flag == FLAG_LAST;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BashPrgStreamDecoder<R> {
+    fn read(&mut self, out: &mut [u8]) -> Result<usize> {
+        while self.pos == self.len && !self.finished {
+            if self.failed {
+                return Err(IoError::new(
+                    ErrorKind::InvalidData,
+                    "stream is not decoded after an error",
+                ));
+            }
+            if let Err(err) = self.open_chunk() {
+                self.failed = true;
+                return Err(err);
+            }
+        }
+        let count = out.len().min(self.len - self.pos);
+        out[..count].copy_from_slice(&self.buf[self.pos..self.pos + count]);
+        self.pos += count;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{copy, Cursor};
+
+    use super::*;
+
+    const KEY: [u8; 16] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+    const NONCE: [u8; NONCE_LEN] = [0xA5; NONCE_LEN];
+
+    /// (l, d) = (128, 1), chunk_len = 16, data[i] = i ^ 0x5C, i < 40.
+    const STREAM_40: [u8; 116] = [
+        0x42, 0x50, 0x53, 0x01, 0x10, 0x01, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0xA5, 0xA5, 0xA5,
+        0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0x9E, 0xA6,
+        0x87, 0x71, 0x04, 0xC1, 0xD5, 0x12, 0x93, 0xB4, 0x0B, 0xEC, 0x52, 0x7C, 0x53, 0xA8, 0x48,
+        0xC2, 0x6A, 0xBF, 0x72, 0xA0, 0xA4, 0xF9, 0xF1, 0xCA, 0x51, 0x95, 0xF7, 0xB5, 0x1D, 0x9C,
+        0x56, 0xC1, 0x4C, 0x55, 0x52, 0x20, 0x5A, 0x8F, 0xD9, 0x8F, 0x2C, 0x2B, 0x6B, 0x9B, 0x6D,
+        0x59, 0xEA, 0x35, 0x95, 0x2F, 0x9C, 0xDF, 0x07, 0x09, 0x01, 0xA8, 0xCC, 0x67, 0x5A, 0x28,
+        0x1F, 0xD5, 0x0E, 0x51, 0x7B, 0xF0, 0x26, 0x99, 0x82, 0x36, 0x4F, 0x99, 0xE4, 0x55, 0xFE,
+        0xAA, 0x8D, 0x08, 0xD5, 0x73, 0x8A, 0x06, 0x1B, 0x0E, 0xFE, 0xE6,
+    ];
+
+    /// (l, d) = (128, 1), chunk_len = 16, empty data.
+    const STREAM_0: [u8; 44] = [
+        0x42, 0x50, 0x53, 0x01, 0x10, 0x01, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0xA5, 0xA5, 0xA5,
+        0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0xA5, 0x18, 0xCF,
+        0x88, 0x15, 0x1B, 0x42, 0x2C, 0xBF, 0x75, 0x75, 0xC9, 0xFD, 0xCF, 0xD5, 0x0A, 0x9E,
+    ];
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8 ^ 0x5C).collect()
+    }
+
+    fn encode(l: usize, d: usize, key: &[u8], chunk_len: usize, data: &[u8]) -> Vec<u8> {
+        let mut encoder =
+            BashPrgStreamEncoder::new(l, d, key, &NONCE, chunk_len, Vec::new()).unwrap();
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn decode(l: usize, d: usize, key: &[u8], stream: &[u8]) -> Result<Vec<u8>> {
+        let mut decoder = BashPrgStreamDecoder::new(l, d, key, Cursor::new(stream))?;
+        let mut data = Vec::new();
+        copy(&mut decoder, &mut data)?;
+        Ok(data)
+    }
+
+    #[test]
+    fn chunked_test_vectors() {
+        assert_eq!(encode(128, 1, &KEY, 16, &data(40)), STREAM_40);
+        assert_eq!(encode(128, 1, &KEY, 16, &[]), STREAM_0);
+        assert_eq!(decode(128, 1, &KEY, &STREAM_40).unwrap(), data(40));
+        assert!(decode(128, 1, &KEY, &STREAM_0).unwrap().is_empty());
+
+        // The format by commands of the automaton.
+        let mut prg = BashPrg::start(128, 1, NONCE, KEY).unwrap();
+        prg.absorb(&STREAM_40[..PARAMS_LEN]);
+        let mut pos = HEADER_LEN;
+        for (i, chunk) in data(40).chunks_mut(16).enumerate() {
+            prg.absorb([(i == 2) as u8]);
+            prg.encr(chunk).unwrap();
+            let mut tag = [0u8; 16];
+            prg.squeeze(&mut tag);
+            prg.ratchet();
+            assert_eq!(STREAM_40[pos..pos + chunk.len()], *chunk);
+            pos += chunk.len();
+            assert_eq!(STREAM_40[pos..pos + 16], tag);
+            pos += 16;
+        }
+        assert_eq!(pos, STREAM_40.len());
+    }
+
+    #[test]
+    fn chunked_test_round_trip() {
+        let key = [0x3Cu8; 32];
+        for &(l, d) in [(128, 1), (192, 2), (256, 2)].iter() {
+            for &len in [0, 1, 99, 100, 101, 300, 1000].iter() {
+                let stream = encode(l, d, &key, 100, &data(len));
+                let chunks = len.max(1).div_ceil(100);
+                assert_eq!(stream.len(), HEADER_LEN + len + chunks * l / 8);
+                assert_eq!(decode(l, d, &key, &stream).unwrap(), data(len));
+            }
+        }
+    }
+
+    #[test]
+    fn chunked_test_damaged() {
+        let is_invalid = |stream: &[u8]| {
+            decode(128, 1, &KEY, stream).unwrap_err().kind() == ErrorKind::InvalidData
+        };
+        // Truncation at chunk boundary and inside chunk.
+        assert!(is_invalid(&STREAM_40[..HEADER_LEN + 64]));
+        assert!(is_invalid(&STREAM_40[..HEADER_LEN + 32]));
+        assert!(is_invalid(&STREAM_40[..HEADER_LEN + 50]));
+        assert!(is_invalid(&STREAM_40[..HEADER_LEN]));
+        // Extension.
+        let mut stream = STREAM_40.to_vec();
+        stream.push(0);
+        assert!(is_invalid(&stream));
+        // Reordering of chunks.
+        let mut stream = STREAM_40.to_vec();
+        stream[HEADER_LEN..HEADER_LEN + 64].rotate_left(32);
+        assert!(is_invalid(&stream));
+        // Modification of ciphertext, tag and header.
+        for &pos in [HEADER_LEN + 1, HEADER_LEN + 20, 6, 12].iter() {
+            let mut stream = STREAM_40.to_vec();
+            stream[pos] ^= 1;
+            assert!(is_invalid(&stream));
+        }
+        // Another key or parameters.
+        assert!(decode(128, 1, &[0u8; 16], &STREAM_40).is_err());
+        assert!(decode(128, 2, &KEY, &STREAM_40).is_err());
+        assert!(decode(128, 1, &KEY, &STREAM_40[..10]).is_err());
+
+        // Verified chunks are returned before the error.
+        let stream = &STREAM_40[..HEADER_LEN + 64];
+        let mut decoder = BashPrgStreamDecoder::new(128, 1, KEY, Cursor::new(stream)).unwrap();
+        let mut buf = [0u8; 40];
+        assert_eq!(decoder.read(&mut buf).unwrap(), 16);
+        assert_eq!(buf[..16], data(16)[..]);
+        assert!(decoder.read(&mut buf).is_err());
+        assert!(decoder.read(&mut buf).is_err());
+    }
+
+    #[test]
+    fn chunked_test_params() {
+        let new = |l, d, key: &[u8], chunk_len| {
+            BashPrgStreamEncoder::new(l, d, key, &NONCE, chunk_len, Vec::new()).is_ok()
+        };
+        assert!(new(128, 1, &KEY, CHUNK_LEN));
+        assert!(new(128, 1, &KEY, MAX_CHUNK_LEN));
+        assert!(!new(128, 1, &KEY, MAX_CHUNK_LEN + 1));
+        assert!(!new(128, 1, &KEY, 0));
+        assert!(!new(128, 1, &[], CHUNK_LEN));
+        assert!(!new(256, 1, &KEY, CHUNK_LEN));
+        assert!(!new(100, 1, &[0u8; 32], CHUNK_LEN));
+    }
+}
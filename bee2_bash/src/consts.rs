@@ -1,4 +1,5 @@
-static C: [u64; 24] = [
+/// Round constants.
+pub(crate) static C: [u64; 24] = [
     0x3BF5080AC8BA94B1,
     0xC1D1659C1BBD92F6,
     0x60E8B2CE0DDEC97B,
@@ -26,12 +27,12 @@ static C: [u64; 24] = [
 ];
 
 #[inline]
-fn p0(x: u64) -> u64 {
+pub(crate) fn p0(x: u64) -> u64 {
     x
 }
 
 #[inline]
-fn p1(x: u64) -> u64 {
+pub(crate) fn p1(x: u64) -> u64 {
     if x < 8 {
         8 + (x + 2 * (x & 1) + 7) % 8
     } else if x < 16 {
@@ -42,26 +43,27 @@ fn p1(x: u64) -> u64 {
 }
 
 #[inline]
-fn p2(x: u64) -> u64 {
+pub(crate) fn p2(x: u64) -> u64 {
     p1(p1(x))
 }
 
 #[inline]
-fn p3(x: u64) -> u64 {
+pub(crate) fn p3(x: u64) -> u64 {
     8 * (x / 8) + (x % 8 + 4) % 8
 }
 
 #[inline]
-fn p4(x: u64) -> u64 {
+pub(crate) fn p4(x: u64) -> u64 {
     p1(p3(x))
 }
 
 #[inline]
-fn p5(x: u64) -> u64 {
+pub(crate) fn p5(x: u64) -> u64 {
     p2(p3(x))
 }
 
 #[inline]
+#[allow(clippy::too_many_arguments)]
 fn bash_s(s: &mut [u64; 24], w0_: usize, w1_: usize, w2_: usize, m1: u8, n1: u8, m2: u8, n2: u8) {
     let mut t0: u64;
     let mut t1: u64;
@@ -281,7 +283,7 @@ macro_rules! bash_r {
 }
 
 #[inline]
-pub fn bash_f0(s: &mut [u64; 24]) {
+fn bash_f0_portable(s: &mut [u64; 24]) {
     bash_r!(s, p0, p1, 1);
     bash_r!(s, p1, p2, 2);
     bash_r!(s, p2, p3, 3);
@@ -308,23 +310,110 @@ pub fn bash_f0(s: &mut [u64; 24]) {
     bash_r!(s, p5, p0, 24);
 }
 
+/// Implementation of `bash-f`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BashFBackend {
+    /// Portable implementation on `u64`.
+    Portable,
+    /// SSE2, x86 and x86-64 only.
+    Sse2,
+    /// AVX2, x86 and x86-64 only.
+    Avx2,
+}
+
+impl BashFBackend {
+    /// The fastest implementation supported by CPU.
+    ///
+    /// SSE2 is used only on x86: on x86-64 it is slower than rotations of `u64`.
+    pub fn detect() -> Self {
+        if BashFBackend::Avx2.is_supported() {
+            BashFBackend::Avx2
+        } else if cfg!(target_arch = "x86") && BashFBackend::Sse2.is_supported() {
+            BashFBackend::Sse2
+        } else {
+            BashFBackend::Portable
+        }
+    }
+
+    /// Whether CPU supports the implementation.
+    pub fn is_supported(self) -> bool {
+        match self {
+            BashFBackend::Portable => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            BashFBackend::Sse2 => std::is_x86_feature_detected!("sse2"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            BashFBackend::Avx2 => std::is_x86_feature_detected!("avx2"),
+            #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+            _ => false,
+        }
+    }
+
+    /// Apply `bash-f` to `s`.
+    ///
+    /// # Panics
+    /// CPU does not support the implementation.
+    pub fn bash_f0(self, s: &mut [u64; 24]) {
+        assert!(self.is_supported(), "{:?} is not supported by CPU", self);
+        match self {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            BashFBackend::Sse2 => unsafe { crate::simd::sse2::bash_f0(s) },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            BashFBackend::Avx2 => unsafe { crate::simd::avx2::bash_f0(s) },
+            _ => bash_f0_portable(s),
+        }
+    }
+}
+
+/// `bash-f` by the fastest implementation supported by CPU.
 #[inline]
-pub fn bash_f(s: &mut [u8; 192]) {
-    if cfg!(feature = "go-faster") {
-        let x: *mut [u64; 24] = s.as_mut_ptr() as *mut [u64; 24];
-        bash_f0(unsafe { x.as_mut().unwrap() });
-    } else {
-        let mut s1: [u64; 24] = [0; 24];
-        for (dst, src) in s1.iter_mut().zip(s.chunks_exact(8)) {
-            *dst = u64::from_le_bytes([
-                src[0], src[1], src[2], src[3], src[4], src[5], src[6], src[7],
-            ]);
+pub fn bash_f0(s: &mut [u64; 24]) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            return unsafe { crate::simd::avx2::bash_f0(s) };
         }
-        bash_f0(&mut s1);
-        for (src, dst) in s1.iter().zip(s.chunks_exact_mut(8)) {
-            dst.clone_from_slice(&src.to_le_bytes());
+        #[cfg(target_arch = "x86")]
+        if std::is_x86_feature_detected!("sse2") {
+            return unsafe { crate::simd::sse2::bash_f0(s) };
         }
     }
+    bash_f0_portable(s)
+}
+
+/// State of `bash-f`: 24 words which are also viewed as 192 octets.
+///
+/// Octets are kept in memory order, so word `i` holds octets `8 * i..8 * i + 8`
+/// in little-endian order only on little-endian targets.
+#[derive(Copy, Clone, Default)]
+pub(crate) struct State([u64; 24]);
+
+impl core::ops::Deref for State {
+    type Target = [u8; 192];
+
+    fn deref(&self) -> &[u8; 192] {
+        // [u64; 24] has the size of [u8; 192] and stricter alignment.
+        unsafe { &*(self.0.as_ptr() as *const [u8; 192]) }
+    }
+}
+
+impl core::ops::DerefMut for State {
+    fn deref_mut(&mut self) -> &mut [u8; 192] {
+        unsafe { &mut *(self.0.as_mut_ptr() as *mut [u8; 192]) }
+    }
+}
+
+/// `bash-f` on the octets of `s`.
+///
+/// Words are converted only on big-endian targets.
+#[inline]
+pub(crate) fn bash_f(s: &mut State) {
+    if cfg!(target_endian = "big") {
+        s.0.iter_mut().for_each(|x| *x = u64::from_le(*x));
+    }
+    bash_f0(&mut s.0);
+    if cfg!(target_endian = "big") {
+        s.0.iter_mut().for_each(|x| *x = x.to_le());
+    }
 }
 
 /// A2. Test
@@ -383,6 +472,43 @@ fn bash_f0_test() {
         0x4972ACD9D976214Bu64.to_be(),
         0x7CED8E3F8B6E058Eu64.to_be(),
     ];
+    let input = s;
     bash_f0(&mut s);
     assert_eq!(s, s_);
+
+    for backend in [
+        BashFBackend::Portable,
+        BashFBackend::Sse2,
+        BashFBackend::Avx2,
+    ] {
+        if backend.is_supported() {
+            let mut s = input;
+            backend.bash_f0(&mut s);
+            assert_eq!(s, s_, "{:?}", backend);
+        }
+    }
+}
+
+#[test]
+fn bash_f0_test_backends() {
+    let mut s = [0u64; 24];
+    let mut x = 0x0123456789ABCDEFu64;
+    for _ in 0..64 {
+        for w in s.iter_mut() {
+            x = x
+                .wrapping_mul(0x5851F42D4C957F2D)
+                .wrapping_add(0x14057B7EF767814F);
+            *w ^= x;
+        }
+        let mut s_ = s;
+        bash_f0_portable(&mut s_);
+        for backend in [BashFBackend::Sse2, BashFBackend::Avx2] {
+            if backend.is_supported() {
+                let mut s1 = s;
+                backend.bash_f0(&mut s1);
+                assert_eq!(s1, s_, "{:?}", backend);
+            }
+        }
+        s = s_;
+    }
 }
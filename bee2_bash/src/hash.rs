@@ -1,14 +1,17 @@
 extern crate bee2_traits;
 
-use crate::consts::bash_f;
+use crate::checkpoint::{self, Header, KIND_HASH, STATE_LEN};
+use crate::consts::{bash_f, State};
+use bee2_core::error::{InvalidLength, StateError};
+use bee2_core::mem::mem_eq;
 pub use bee2_traits::Hasher;
 
 #[derive(Clone)]
 struct BashState {
     /// Current state.
-    s: [u8; 192],
+    s: State,
     /// Copy of s1.
-    s1: [u8; 192],
+    s1: State,
     /// Size(length) of buffer.
     buff_len: usize,
     /// Current position in buffer.
@@ -20,28 +23,39 @@ struct Bash {
     state: BashState,
 }
 
+/// `bash-hash` with security level chosen at runtime.
+///
+/// The hash-value is l / 4 octets, `BashHash::new(128)` gives the same
+/// hash-values as `Bash256`.
+#[derive(Clone)]
+pub struct BashHash {
+    bash: Bash,
+}
+
 #[derive(Clone)]
 pub struct Bash256 {
-    hash_size: usize,
     bash: Bash,
 }
 
 #[derive(Clone)]
 pub struct Bash384 {
-    hash_size: usize,
     bash: Bash,
 }
 #[derive(Clone)]
 pub struct Bash512 {
-    hash_size: usize,
     bash: Bash,
 }
 
 impl BashState {
+    /// Return to the state right after `Bash::bash_start()`.
+    #[cfg_attr(not(feature = "rust-crypto"), allow(dead_code))]
     fn reset(&mut self) {
+        // l = 2 * (192 - buff_len)
+        let l = 2 * (192 - self.buff_len);
         self.pos = 0;
         self.s.iter_mut().for_each(|x| *x = 0);
         self.s1.iter_mut().for_each(|x| *x = 0);
+        self.s[192 - 8] = (l / 4) as u8;
     }
 }
 
@@ -51,20 +65,20 @@ impl Default for Bash {
             state: BashState {
                 buff_len: 192 - 256 / 2,
                 pos: 0,
-                s: [0; 192],
-                s1: [0; 192],
+                s: State::default(),
+                s1: State::default(),
             },
         }
     }
 }
 
 impl Bash {
-    fn bash_start(l: usize) -> Result<Self, String> {
-        if l % 16 != 0 || l > 256 {
-            return Err(format!("Incorrect param of security {:}", l));
+    fn bash_start(l: usize) -> Result<Self, InvalidLength> {
+        if l == 0 || l % 16 != 0 || l > 256 {
+            return Err(InvalidLength);
         }
 
-        let mut s = [0; 192];
+        let mut s = State::default();
         s[192 - 8] = (l / 4) as u8;
 
         Ok(Bash {
@@ -72,7 +86,7 @@ impl Bash {
                 buff_len: 192 - l / 2,
                 pos: 0,
                 s,
-                s1: [0; 192],
+                s1: State::default(),
             },
         })
     }
@@ -135,8 +149,14 @@ impl Bash {
     }
 
     fn step_v(&mut self, hash: impl AsRef<[u8]>) -> bool {
+        let hash = hash.as_ref();
+        // hash.len() == l / 4 == (192 - buff_len) / 2
+        let hash_size = (192 - self.state.buff_len) / 2;
+        if hash.len() != hash_size {
+            return false;
+        }
         self.step_g_internal();
-        return self.state.s1 == hash.as_ref();
+        mem_eq(&self.state.s1[..hash_size], hash)
     }
 
     fn hash(l: usize, hash: &mut [u8], src: impl AsRef<[u8]>) {
@@ -149,7 +169,6 @@ impl Bash {
 impl Hasher for Bash256 {
     fn new() -> Self {
         Bash256 {
-            hash_size: 128 / 4,
             bash: Bash::new(128),
         }
     }
@@ -178,7 +197,6 @@ impl Hasher for Bash256 {
 impl Hasher for Bash384 {
     fn new() -> Self {
         Bash384 {
-            hash_size: 192 / 4,
             bash: Bash::new(192),
         }
     }
@@ -207,7 +225,6 @@ impl Hasher for Bash384 {
 impl Hasher for Bash512 {
     fn new() -> Self {
         Bash512 {
-            hash_size: 256 / 4,
             bash: Bash::new(256),
         }
     }
@@ -225,7 +242,7 @@ impl Hasher for Bash512 {
     }
 
     fn output_size() -> usize {
-        256 / 2
+        256 / 4
     }
 
     fn hash(hash: &mut [u8], src: impl AsRef<[u8]>) {
@@ -233,53 +250,273 @@ impl Hasher for Bash512 {
     }
 }
 
+impl Bash {
+    fn export_state(&self) -> [u8; STATE_LEN] {
+        let header = Header {
+            kind: KIND_HASH,
+            l: self.level(),
+            d: 0,
+            flags: 0,
+            pos: self.state.pos,
+        };
+        checkpoint::encode(&header, &self.state.s)
+    }
+
+    fn import_state(state: &[u8]) -> Result<Self, StateError> {
+        let (header, s) = checkpoint::decode(state, KIND_HASH)?;
+        if header.d != 0 || header.flags != 0 {
+            return Err(StateError::InvalidFormat);
+        }
+        let mut bash = Bash::bash_start(header.l).map_err(|_| StateError::InvalidFormat)?;
+        if header.pos >= bash.state.buff_len {
+            return Err(StateError::InvalidFormat);
+        }
+        bash.state.s = s;
+        bash.state.pos = header.pos;
+        Ok(bash)
+    }
+
+    /// Security level.
+    fn level(&self) -> usize {
+        2 * (192 - self.state.buff_len)
+    }
+}
+
+macro_rules! bash_checkpoint {
+    ($bash_x: ident, $l: expr) => {
+        impl $bash_x {
+            /// Export state to continue hashing later, see `checkpoint`.
+            pub fn export_state(&self) -> [u8; STATE_LEN] {
+                self.bash.export_state()
+            }
+
+            /// Import state exported by `export_state()` of the same type.
+            ///
+            /// # Arguments
+            ///
+            /// * state - exported state, state.len() == STATE_LEN
+            pub fn import_state(state: impl AsRef<[u8]>) -> Result<Self, StateError> {
+                let bash = Bash::import_state(state.as_ref())?;
+                if bash.level() != $l {
+                    return Err(StateError::TypeMismatch);
+                }
+                Ok($bash_x { bash })
+            }
+        }
+    };
+}
+bash_checkpoint!(Bash256, 128);
+bash_checkpoint!(Bash384, 192);
+bash_checkpoint!(Bash512, 256);
+
+impl BashHash {
+    /// Hasher initializing.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l % 16 == 0 && 0 < l <= 256
+    pub fn new(l: usize) -> Result<Self, InvalidLength> {
+        Ok(BashHash {
+            bash: Bash::bash_start(l)?,
+        })
+    }
+
+    /// Get security level.
+    pub fn level(&self) -> usize {
+        self.bash.level()
+    }
+
+    /// Get output size of the hasher, l / 4 octets.
+    pub fn output_size(&self) -> usize {
+        self.level() / 4
+    }
+
+    /// Hashing block of data.
+    /// Hash-value from state is recalculated with new data.
+    pub fn step_h(&mut self, buf: impl AsRef<[u8]>) {
+        self.bash.step_h(buf);
+    }
+
+    /// Getting of hash-value.
+    ///
+    /// # Arguments
+    ///
+    /// * hash - to store first hash.len() octets of hash-value, hash.len() <= l / 4
+    ///
+    /// # Panics
+    /// `hash.len() > l / 4`
+    pub fn step_g(&mut self, hash: &mut [u8]) {
+        assert!(
+            hash.len() <= self.output_size(),
+            "hash is longer than l / 4"
+        );
+        self.bash.step_g(hash);
+    }
+
+    /// Verification of hash-value.
+    ///
+    /// Returns `false` if `hash.len() != l / 4`.
+    pub fn step_v(&mut self, hash: impl AsRef<[u8]>) -> bool {
+        self.bash.step_v(hash)
+    }
+
+    /// Hashing of `src` in one call.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l % 16 == 0 && 0 < l <= 256
+    /// * hash - to store hash-value, hash.len() == l / 4
+    /// * src - data to hash.
+    pub fn hash(l: usize, hash: &mut [u8], src: impl AsRef<[u8]>) -> Result<(), InvalidLength> {
+        let mut hasher = BashHash::new(l)?;
+        if hash.len() != hasher.output_size() {
+            return Err(InvalidLength);
+        }
+        hasher.step_h(src);
+        hasher.step_g(hash);
+        Ok(())
+    }
+
+    /// Export state to continue hashing later, see `checkpoint`.
+    pub fn export_state(&self) -> [u8; STATE_LEN] {
+        self.bash.export_state()
+    }
+
+    /// Import state exported by `export_state()` of `BashHash` or `Bash256/384/512`.
+    ///
+    /// The security level is taken from the state.
+    ///
+    /// # Arguments
+    ///
+    /// * state - exported state, state.len() == STATE_LEN
+    pub fn import_state(state: impl AsRef<[u8]>) -> Result<Self, StateError> {
+        Ok(BashHash {
+            bash: Bash::import_state(state.as_ref())?,
+        })
+    }
+}
+
 #[cfg(feature = "rust-crypto")]
 mod crypto {
-    use digest::generic_array::typenum::{U32, U48, U64};
-    use digest::generic_array::GenericArray;
-    pub use digest::{self, Digest};
-    use digest::{BlockInput, FixedOutputDirty, Reset, Update};
+    use digest::consts::{U128, U32, U48, U64, U96};
+    use digest::{
+        FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update,
+    };
 
     use crate::hash::bee2_traits::Hasher;
     use crate::hash::{Bash256, Bash384, Bash512};
 
     macro_rules! bash_crypto {
-        ($bash_x: ty, $size: ty) => {
+        ($bash_x: ty, $size: ty, $block_size: ty) => {
             impl Default for $bash_x {
                 fn default() -> Self {
                     <$bash_x as Hasher>::new()
                 }
             }
 
-            impl BlockInput for $bash_x {
-                type BlockSize = $size;
+            impl HashMarker for $bash_x {}
+
+            impl digest::core_api::BlockSizeUser for $bash_x {
+                type BlockSize = $block_size;
+            }
+
+            impl OutputSizeUser for $bash_x {
+                type OutputSize = $size;
             }
 
             impl Update for $bash_x {
-                fn update(&mut self, input: impl AsRef<[u8]>) {
-                    self.step_h(input)
+                fn update(&mut self, data: &[u8]) {
+                    self.step_h(data)
                 }
             }
 
-            impl FixedOutputDirty for $bash_x {
-                type OutputSize = $size;
+            impl FixedOutput for $bash_x {
+                fn finalize_into(mut self, out: &mut Output<Self>) {
+                    self.step_g(out);
+                }
+            }
 
-                fn finalize_into_dirty(&mut self, out: &mut GenericArray<u8, $size>) {
+            impl FixedOutputReset for $bash_x {
+                fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
                     self.step_g(out);
+                    self.bash.state.reset();
                 }
             }
+
             impl Reset for $bash_x {
                 fn reset(&mut self) {
                     self.bash.state.reset();
                 }
             }
+        };
+    }
+    bash_crypto!(Bash256, U32, U128);
+    bash_crypto!(Bash384, U48, U96);
+    bash_crypto!(Bash512, U64, U64);
+}
+
+#[cfg(feature = "std")]
+mod io {
+    use std::io::{Result, Write};
 
-            //digest::impl_write!($bash_x);
+    use crate::hash::bee2_traits::Hasher;
+    use crate::hash::{Bash256, Bash384, Bash512, BashHash};
+
+    macro_rules! bash_io {
+        ($bash_x: ty) => {
+            impl Write for $bash_x {
+                fn write(&mut self, buf: &[u8]) -> Result<usize> {
+                    self.step_h(buf);
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> Result<()> {
+                    Ok(())
+                }
+            }
+        };
+    }
+    bash_io!(Bash256);
+    bash_io!(Bash384);
+    bash_io!(Bash512);
+    bash_io!(BashHash);
+}
+
+#[cfg(feature = "zeroize")]
+mod wipe {
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+
+    use crate::hash::{Bash, Bash256, Bash384, Bash512, BashHash};
+
+    impl Zeroize for Bash {
+        fn zeroize(&mut self) {
+            self.state.s.zeroize();
+            self.state.s1.zeroize();
+            self.state.pos.zeroize();
+        }
+    }
+
+    impl Drop for Bash {
+        fn drop(&mut self) {
+            self.zeroize();
+        }
+    }
+
+    macro_rules! bash_wipe {
+        ($bash_x: ty) => {
+            impl Zeroize for $bash_x {
+                fn zeroize(&mut self) {
+                    self.bash.zeroize();
+                }
+            }
+
+            impl ZeroizeOnDrop for $bash_x {}
         };
     }
-    bash_crypto!(Bash256, U32);
-    bash_crypto!(Bash384, U48);
-    bash_crypto!(Bash512, U64);
+    bash_wipe!(Bash256);
+    bash_wipe!(Bash384);
+    bash_wipe!(Bash512);
+    bash_wipe!(BashHash);
 }
 
 #[cfg(test)]
@@ -372,6 +609,22 @@ mod test {
         Bash256::hash(&mut hash, &s[..135]);
         assert_eq!(hash, unsafe { *(l_128_3.as_ptr() as *const [u8; 32]) });
     }
+    #[test]
+    fn hash_test_step_v() {
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let mut hash: [u8; 48] = [0; 48];
+        Bash384::hash(&mut hash, &s[..95]);
+
+        let mut hasher = Bash384::new();
+        hasher.step_h(&s[..95]);
+        assert!(hasher.step_v(hash));
+        assert!(!hasher.step_v(&hash[..16]));
+        assert!(!hasher.step_v([]));
+        hash[47] ^= 1;
+        assert!(!hasher.step_v(hash));
+        assert!(!hasher.step_v([0; 49]));
+    }
+
     #[test]
     fn hash_test_194_0() {
         let l_194_0 = [
@@ -513,4 +766,166 @@ mod test {
             ]
         );
     }
+
+    #[cfg(feature = "rust-crypto")]
+    #[test]
+    fn test_digest_reset() {
+        use digest::{Digest, FixedOutputReset};
+
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let mut hasher = <Bash256 as Digest>::new();
+        Digest::update(&mut hasher, b"garbage");
+        Digest::reset(&mut hasher);
+        Digest::update(&mut hasher, &s[..100]);
+        Digest::update(&mut hasher, &s[100..127]);
+        let first = hasher.finalize_fixed_reset();
+        Digest::update(&mut hasher, &s[..127]);
+        let second = hasher.finalize();
+
+        let mut hash = [0u8; 32];
+        Bash256::hash(&mut hash, &s[..127]);
+        assert_eq!(first[..], hash[..]);
+        assert_eq!(second[..], hash[..]);
+    }
+
+    #[cfg(feature = "rust-crypto")]
+    #[test]
+    fn test_hmac() {
+        use hmac::{Mac, SimpleHmac};
+
+        let key = [0x0Bu8; 20];
+        let data = b"Hi There";
+
+        let mut mac = <SimpleHmac<Bash384> as Mac>::new_from_slice(&key).unwrap();
+        mac.update(data);
+        let tag = mac.finalize().into_bytes();
+
+        // HMAC by definition, block size of bash384 is 96 octets.
+        let mut ipad = [0x36u8; 96];
+        let mut opad = [0x5Cu8; 96];
+        ipad.iter_mut().zip(key.iter()).for_each(|(x, k)| *x ^= k);
+        opad.iter_mut().zip(key.iter()).for_each(|(x, k)| *x ^= k);
+        let mut inner = [0u8; 48];
+        let mut hasher = <Bash384 as Hasher>::new();
+        hasher.step_h(ipad);
+        hasher.step_h(data);
+        hasher.step_g(&mut inner);
+        let mut outer = [0u8; 48];
+        let mut hasher = <Bash384 as Hasher>::new();
+        hasher.step_h(opad);
+        hasher.step_h(inner);
+        hasher.step_g(&mut outer);
+
+        assert_eq!(tag[..], outer[..]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_io_write() {
+        use std::io::{copy, Cursor};
+
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let mut hasher = Bash384::new();
+        copy(&mut Cursor::new(&s[..150]), &mut hasher).unwrap();
+        let mut hash = [0u8; 48];
+        hasher.step_g(&mut hash);
+
+        let mut expected = [0u8; 48];
+        Bash384::hash(&mut expected, &s[..150]);
+        assert_eq!(hash, expected);
+    }
+
+    #[test]
+    fn hash_test_checkpoint() {
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        for split in [0, 1, 95, 96, 97, 150].iter() {
+            let mut hasher = Bash256::new();
+            hasher.step_h(&s[..*split]);
+            let state = hasher.export_state();
+
+            let mut hasher = Bash256::import_state(state).unwrap();
+            hasher.step_h(&s[*split..]);
+            let mut hash = [0u8; 32];
+            hasher.step_g(&mut hash);
+            let mut expected = [0u8; 32];
+            Bash256::hash(&mut expected, &s[..]);
+            assert_eq!(hash, expected);
+        }
+
+        let mut hasher = Bash512::new();
+        hasher.step_h(&s[..100]);
+        let state = hasher.export_state();
+        let mut hasher = Bash512::import_state(&state[..]).unwrap();
+        hasher.step_h(&s[100..]);
+        let mut hash = [0u8; 64];
+        hasher.step_g(&mut hash);
+        let mut expected = [0u8; 64];
+        Bash512::hash(&mut expected, &s[..]);
+        assert_eq!(hash, expected);
+
+        assert_eq!(
+            Bash384::import_state(state).err(),
+            Some(StateError::TypeMismatch)
+        );
+    }
+
+    #[test]
+    fn hash_test_any_level() {
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+
+        let mut hash = [0u8; 32];
+        BashHash::hash(128, &mut hash, &s[..150]).unwrap();
+        let mut expected = [0u8; 32];
+        Bash256::hash(&mut expected, &s[..150]);
+        assert_eq!(hash, expected);
+
+        let mut hash = [0u8; 64];
+        let mut hasher = BashHash::new(256).unwrap();
+        hasher.step_h(&s[..100]);
+        hasher.step_h(&s[100..]);
+        hasher.step_g(&mut hash);
+        let mut expected = [0u8; 64];
+        Bash512::hash(&mut expected, &s[..]);
+        assert_eq!(hash, expected);
+
+        // l = 160: 40 octets, block of 112 octets.
+        let mut hasher = BashHash::new(160).unwrap();
+        assert_eq!(hasher.level(), 160);
+        assert_eq!(hasher.output_size(), 40);
+        hasher.step_h(&s[..111]);
+        let state = hasher.export_state();
+        hasher.step_h(&s[111..]);
+        let mut hash = [0u8; 40];
+        hasher.step_g(&mut hash);
+        assert!(hasher.step_v(hash));
+        assert!(!hasher.step_v(&hash[..20]));
+        assert!(!hasher.step_v([]));
+        let mut one = [0u8; 40];
+        BashHash::hash(160, &mut one, &s[..]).unwrap();
+        assert_eq!(hash, one);
+        let mut resumed = BashHash::import_state(state).unwrap();
+        resumed.step_h(&s[111..]);
+        assert!(resumed.step_v(hash));
+        assert_ne!(hash[..32], expected[..32]);
+        assert_eq!(
+            Bash256::import_state(state).err(),
+            Some(StateError::TypeMismatch)
+        );
+
+        // States of fixed levels are imported.
+        let mut hasher = Bash384::new();
+        hasher.step_h(&s[..10]);
+        assert_eq!(
+            BashHash::import_state(hasher.export_state())
+                .unwrap()
+                .level(),
+            192
+        );
+
+        for l in [0, 8, 100, 272].iter() {
+            assert!(BashHash::new(*l).is_err());
+        }
+        assert!(BashHash::hash(160, &mut [0u8; 32], []).is_err());
+        assert!(BashHash::new(16).is_ok());
+    }
 }
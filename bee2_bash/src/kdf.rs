@@ -0,0 +1,268 @@
+//! Key derivation by `BashPrg`.
+//!
+//! A secret is extracted into the automaton once and then expanded into any
+//! number of subkeys distinguished by labels:
+//!
+//! ```text
+//! extract: start(l, 2, ANN, secret), absorb(salt), ratchet()
+//! expand:  absorb(label), absorb(<out.len()>_32 || context), squeeze(out)
+//! ```
+//!
+//! A secret which is not a valid key of level l (a Diffie-Hellman output of
+//! 35 or 66 octets, for example) is absorbed by the automaton started without
+//! a key instead: `start(l, 2, ANN, []), absorb(secret)`. A valid key has
+//! `len % 4 == 0 && l / 8 <= len <= 60`. The key length is loaded into the
+//! state by `start`, so both ways never give the same state.
+//!
+//! Every `expand` starts from the extracted state, numbers are little-endian.
+//! The length of output is loaded, so outputs of different lengths are not
+//! prefixes of each other.
+
+use crate::prg::{BashPrg, InvalidLength, PrgAbsorb, PrgRatchet, PrgSqueeze, PrgStart};
+
+/// Annotation of the automaton.
+pub const ANN: &[u8; 8] = b"bashkdf\x01";
+/// Capacity of the automaton.
+const CAPACITY: usize = 2;
+/// Maximal length of secret loaded as key.
+const MAX_KEY_LEN: usize = 60;
+/// Maximal length of label.
+pub const MAX_LABEL_LEN: usize = 255;
+/// Maximal length of output of one `expand`.
+pub const MAX_OUT_LEN: usize = 1 << 16;
+
+/// Extracted secret.
+#[derive(Clone)]
+pub struct BashPrgKdf {
+    prg: BashPrg,
+}
+
+impl BashPrgKdf {
+    /// Extract `secret` with `salt`.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l == 128 || l == 192 || l == 256
+    /// * secret - shared secret of any non-zero length.
+    /// * salt - salt of any length, may be empty.
+    pub fn extract(
+        l: usize,
+        secret: impl AsRef<[u8]>,
+        salt: impl AsRef<[u8]>,
+    ) -> Result<Self, InvalidLength> {
+        let secret = secret.as_ref();
+        if secret.is_empty() {
+            return Err(InvalidLength);
+        }
+        let is_key = secret.len() % 4 == 0 && secret.len() >= l / 8 && secret.len() <= MAX_KEY_LEN;
+        let mut prg = if is_key {
+            BashPrg::start(l, CAPACITY, ANN, secret)?
+        } else {
+            let mut prg = BashPrg::start(l, CAPACITY, ANN, [])?;
+            prg.absorb(secret);
+            prg
+        };
+        prg.absorb(salt);
+        prg.ratchet();
+        Ok(Self { prg })
+    }
+
+    /// Derive `out` for `label` and `context`.
+    ///
+    /// # Arguments
+    ///
+    /// * label - purpose of output, 0 < label.len() <= MAX_LABEL_LEN
+    /// * context - data to bind output to (identifiers, transcript), may be empty.
+    /// * out - to store output, 0 < out.len() <= MAX_OUT_LEN
+    pub fn expand(
+        &self,
+        label: impl AsRef<[u8]>,
+        context: impl AsRef<[u8]>,
+        out: &mut [u8],
+    ) -> Result<(), InvalidLength> {
+        let label = label.as_ref();
+        if label.is_empty() || label.len() > MAX_LABEL_LEN {
+            return Err(InvalidLength);
+        }
+        if out.is_empty() || out.len() > MAX_OUT_LEN {
+            return Err(InvalidLength);
+        }
+
+        #[cfg_attr(not(feature = "zeroize"), allow(clippy::clone_on_copy))]
+        let mut prg = self.prg.clone();
+        prg.absorb(label);
+        prg.absorb_start();
+        prg.absorb_step((out.len() as u32).to_le_bytes());
+        prg.absorb_step(context);
+        prg.squeeze(out);
+        Ok(())
+    }
+
+    /// Extract and expand in one call.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l == 128 || l == 192 || l == 256
+    /// * secret - shared secret of any non-zero length.
+    /// * salt - salt of any length, may be empty.
+    /// * label - purpose of output, 0 < label.len() <= MAX_LABEL_LEN
+    /// * context - data to bind output to, may be empty.
+    /// * out - to store output, 0 < out.len() <= MAX_OUT_LEN
+    pub fn derive(
+        l: usize,
+        secret: impl AsRef<[u8]>,
+        salt: impl AsRef<[u8]>,
+        label: impl AsRef<[u8]>,
+        context: impl AsRef<[u8]>,
+        out: &mut [u8],
+    ) -> Result<(), InvalidLength> {
+        Self::extract(l, secret, salt)?.expand(label, context, out)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+mod wipe {
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+
+    use super::*;
+
+    impl Zeroize for BashPrgKdf {
+        fn zeroize(&mut self) {
+            self.prg.zeroize();
+        }
+    }
+
+    impl ZeroizeOnDrop for BashPrgKdf {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SECRET: [u8; 32] = [
+        0xB1, 0x94, 0xBA, 0xC8, 0x0A, 0x08, 0xF5, 0x3B, 0x36, 0x6D, 0x00, 0x8E, 0x58, 0x4A, 0x5D,
+        0xE4, 0x85, 0x04, 0xFA, 0x9D, 0x1B, 0xB6, 0xC7, 0xAC, 0x25, 0x2E, 0x72, 0xC2, 0x02, 0xFD,
+        0xCE, 0x0D,
+    ];
+
+    #[test]
+    fn kdf_test() {
+        let mut expected = [0u8; 100];
+        let mut prg = BashPrg::start(256, 2, ANN, SECRET).unwrap();
+        prg.absorb(b"salt");
+        prg.ratchet();
+        prg.absorb(b"enc");
+        let mut context = (100u32).to_le_bytes().to_vec();
+        context.extend_from_slice(b"alice bob");
+        prg.absorb(context);
+        prg.squeeze(&mut expected);
+
+        let mut out = [0u8; 100];
+        let kdf = BashPrgKdf::extract(256, SECRET, b"salt").unwrap();
+        kdf.expand(b"enc", b"alice bob", &mut out).unwrap();
+        assert_eq!(out[..], expected[..]);
+
+        let mut one = [0u8; 100];
+        BashPrgKdf::derive(256, SECRET, b"salt", b"enc", b"alice bob", &mut one).unwrap();
+        assert_eq!(one[..], out[..]);
+
+        // Labels, contexts, salts and lengths give independent outputs.
+        let mut other = [0u8; 100];
+        kdf.expand(b"mac", b"alice bob", &mut other).unwrap();
+        assert_ne!(other[..], out[..]);
+        kdf.expand(b"enc", b"alice eve", &mut other).unwrap();
+        assert_ne!(other[..], out[..]);
+        kdf.expand(b"enca", b"lice bob", &mut other).unwrap();
+        assert_ne!(other[..], out[..]);
+        BashPrgKdf::derive(256, SECRET, b"pepper", b"enc", b"alice bob", &mut other).unwrap();
+        assert_ne!(other[..], out[..]);
+        kdf.expand(b"enc", b"alice bob", &mut other[..32]).unwrap();
+        assert_ne!(other[..32], out[..32]);
+    }
+
+    /// Output of `extract` by absorbing `secret` without a key.
+    fn absorbed(l: usize, secret: &[u8]) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        let mut prg = BashPrg::start(l, 2, ANN, []).unwrap();
+        prg.absorb(secret);
+        prg.absorb(b"salt");
+        prg.ratchet();
+        prg.absorb(b"enc");
+        prg.absorb((32u32).to_le_bytes());
+        prg.squeeze(&mut out);
+        out
+    }
+
+    #[test]
+    fn kdf_test_long_secret() {
+        let mut secret = [0u8; 66];
+        secret[..32].copy_from_slice(&SECRET);
+        secret[32..64].copy_from_slice(&SECRET);
+
+        let mut out = [0u8; 32];
+        BashPrgKdf::derive(256, &secret[..64], b"salt", b"enc", [], &mut out).unwrap();
+        assert_eq!(out, absorbed(256, &secret[..64]));
+
+        // Every octet of the secret is used.
+        let mut other = [0u8; 32];
+        for &len in [66, 63, 61].iter() {
+            BashPrgKdf::derive(256, &secret[..len], b"salt", b"enc", [], &mut other).unwrap();
+            assert_eq!(other, absorbed(256, &secret[..len]));
+            assert_ne!(other, out);
+        }
+        BashPrgKdf::derive(128, &secret[..61], b"salt", b"enc", [], &mut out).unwrap();
+        assert_eq!(out, absorbed(128, &secret[..61]));
+        assert_ne!(other, out);
+    }
+
+    #[test]
+    fn kdf_test_odd_secret() {
+        let mut secret = [0u8; 60];
+        secret[..32].copy_from_slice(&SECRET);
+        secret[32..].copy_from_slice(&SECRET[..28]);
+
+        // Secrets which are not keys of the level are absorbed.
+        let mut out = [0u8; 32];
+        for &(l, len) in [
+            (128, 12),
+            (128, 1),
+            (128, 35),
+            (192, 26),
+            (256, 16),
+            (256, 33),
+        ]
+        .iter()
+        {
+            BashPrgKdf::derive(l, &secret[..len], b"salt", b"enc", [], &mut out).unwrap();
+            assert_eq!(out, absorbed(l, &secret[..len]));
+        }
+        for &len in [50, 59].iter() {
+            BashPrgKdf::derive(256, &secret[..len], b"salt", b"enc", [], &mut out).unwrap();
+            assert_eq!(out, absorbed(256, &secret[..len]));
+        }
+
+        // Keys are loaded, so both ways differ for the same octets.
+        for &(l, len) in [(128, 16), (192, 24), (256, 32), (256, 60)].iter() {
+            BashPrgKdf::derive(l, &secret[..len], b"salt", b"enc", [], &mut out).unwrap();
+            assert_ne!(out, absorbed(l, &secret[..len]));
+        }
+    }
+
+    #[test]
+    fn kdf_test_limits() {
+        let kdf = BashPrgKdf::extract(128, &SECRET[..16], []).unwrap();
+        let mut out = vec![0u8; MAX_OUT_LEN + 1];
+        assert!(kdf.expand([7u8; MAX_LABEL_LEN], [], &mut out[..1]).is_ok());
+        assert!(kdf
+            .expand([7u8; MAX_LABEL_LEN + 1], [], &mut out[..1])
+            .is_err());
+        assert!(kdf.expand([], [], &mut out[..1]).is_err());
+        assert!(kdf.expand(b"key", [], &mut out[..MAX_OUT_LEN]).is_ok());
+        assert!(kdf.expand(b"key", [], &mut out).is_err());
+        assert!(kdf.expand(b"key", [], &mut []).is_err());
+
+        assert!(BashPrgKdf::extract(128, [], []).is_err());
+        assert!(BashPrgKdf::extract(160, &SECRET[..20], []).is_err());
+        assert!(BashPrgKdf::extract(160, &SECRET[..3], []).is_err());
+    }
+}
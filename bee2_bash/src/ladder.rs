@@ -0,0 +1,435 @@
+//! Key ladder, generalization of Block 8.11 Programming.
+//!
+//! A ladder is a tree of automata. Every node has a level `(l, d)` and a list
+//! of branches, a branch either encrypts an output or derives the key of a
+//! child node. The automaton of a node is started with the annotation of its
+//! first branch, the first branch uses it as is and branch `i > 0` restarts a
+//! copy of it:
+//!
+//! ```text
+//! sigma   <- start(l, d, ann_1, key)
+//! gamma_1 <- sigma
+//! gamma_i <- sigma restarted with (ann_i, []), i > 1
+//! output:  y_i <- encr(y_i) by gamma_i
+//! child:   key_i <- squeeze() by gamma_i, the child is started with key_i
+//! ```
+//!
+//! The root also loads an identifier: `absorb(id), ratchet()` after `start`.
+//! Child keys and outputs are listed in depth-first order of branches.
+//!
+//! `programming()` is the ladder `(256, 2)` -> `(128, 1)` with two outputs.
+
+use bee2_core::error::LadderError;
+
+use crate::prg::{BashPrg, PrgAbsorb, PrgEncr, PrgRatchet, PrgRestart, PrgSqueeze, PrgStart};
+
+/// Branch of node.
+#[derive(Clone, Debug)]
+enum Branch<'a> {
+    /// Encrypted output.
+    Output(&'a [u8]),
+    /// Child node and its key.
+    Child(&'a [u8], KeyLadder<'a>),
+}
+
+impl<'a> Branch<'a> {
+    fn ann(&self) -> &'a [u8] {
+        match *self {
+            Branch::Output(ann) | Branch::Child(ann, _) => ann,
+        }
+    }
+}
+
+/// Node of key ladder, the root is the ladder itself.
+///
+/// All lengths are checked by `derive()` before any output is written.
+#[derive(Clone, Debug)]
+pub struct KeyLadder<'a> {
+    /// Security level and capacity.
+    level: (usize, usize),
+    /// Branches in order.
+    branches: Vec<Branch<'a>>,
+}
+
+fn check_level(l: usize, d: usize) -> Result<(), LadderError> {
+    if (l != 128 && l != 192 && l != 256) || (d != 1 && d != 2) {
+        return Err(LadderError::InvalidLevel);
+    }
+    Ok(())
+}
+
+fn check_ann(ann: &[u8]) -> Result<(), LadderError> {
+    if ann.len() % 4 != 0 || ann.len() > 60 {
+        return Err(LadderError::InvalidAnnotation);
+    }
+    Ok(())
+}
+
+fn is_key_len(l: usize, len: usize) -> bool {
+    len % 4 == 0 && len <= 60 && len >= l / 8
+}
+
+impl<'a> KeyLadder<'a> {
+    /// Node without branches.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l == 128 || l == 192 || l == 256
+    /// * d - capacity, d == 1 || d == 2
+    pub fn new(l: usize, d: usize) -> Self {
+        Self {
+            level: (l, d),
+            branches: Vec::new(),
+        }
+    }
+
+    /// Ladder of Block 8.11: root `(256, 2)`, child `(128, 1)` with outputs `a1`, `a2`.
+    pub fn programming(a1: &'a [u8], a2: &'a [u8]) -> Self {
+        Self::new(256, 2).child(&[], Self::new(128, 1).branch(a1).branch(a2))
+    }
+
+    /// Add branch which encrypts an output.
+    ///
+    /// # Arguments
+    ///
+    /// * ann - annotation, ann.len() % 4 == 0 && ann.len() <= 60
+    pub fn branch(mut self, ann: &'a [u8]) -> Self {
+        self.branches.push(Branch::Output(ann));
+        self
+    }
+
+    /// Add branch which derives the key of `child`.
+    ///
+    /// # Arguments
+    ///
+    /// * ann - annotation, ann.len() % 4 == 0 && ann.len() <= 60
+    /// * child - child node.
+    pub fn child(mut self, ann: &'a [u8], child: KeyLadder<'a>) -> Self {
+        self.branches.push(Branch::Child(ann, child));
+        self
+    }
+
+    /// Numbers of child keys and outputs of the tree.
+    fn counts(&self) -> (usize, usize) {
+        self.branches
+            .iter()
+            .fold((0, 0), |(keys, outputs), branch| match branch {
+                Branch::Output(_) => (keys, outputs + 1),
+                Branch::Child(_, child) => {
+                    let (k, o) = child.counts();
+                    (keys + 1 + k, outputs + o)
+                }
+            })
+    }
+
+    /// Check levels and annotations of the tree and lengths of child keys.
+    fn check_node<'k>(
+        &self,
+        child_key_lens: &mut impl Iterator<Item = &'k usize>,
+    ) -> Result<(), LadderError> {
+        check_level(self.level.0, self.level.1)?;
+        if self.branches.is_empty() {
+            return Err(LadderError::NoBranches);
+        }
+        for branch in self.branches.iter() {
+            check_ann(branch.ann())?;
+            if let Branch::Child(_, child) = branch {
+                let len = *child_key_lens.next().expect("number of keys is checked");
+                if !is_key_len(child.level.0, len) {
+                    return Err(LadderError::InvalidChildKeyLength);
+                }
+                child.check_node(child_key_lens)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check parameters of the tree and lengths of buffers.
+    ///
+    /// # Arguments
+    ///
+    /// * key_len - length of key, key_len % 4 == 0 && l / 8 <= key_len <= 60
+    /// * child_key_lens - lengths of child keys in depth-first order, every
+    ///   len % 4 == 0 && l1 / 8 <= len <= 60 for level l1 of the child.
+    /// * outputs - number of outputs in the tree.
+    pub fn check(
+        &self,
+        key_len: usize,
+        child_key_lens: &[usize],
+        outputs: usize,
+    ) -> Result<(), LadderError> {
+        let (keys, expected) = self.counts();
+        if child_key_lens.len() != keys {
+            return Err(LadderError::ChildKeyCountMismatch {
+                expected: keys,
+                actual: child_key_lens.len(),
+            });
+        }
+        if outputs != expected {
+            return Err(LadderError::BranchCountMismatch {
+                expected,
+                actual: outputs,
+            });
+        }
+        self.check_node(&mut child_key_lens.iter())?;
+        if !is_key_len(self.level.0, key_len) {
+            return Err(LadderError::InvalidKeyLength);
+        }
+        Ok(())
+    }
+
+    /// Process branches of the node started as `sigma`.
+    fn derive_node(
+        &self,
+        sigma: &BashPrg,
+        child_keys: &mut core::slice::IterMut<'_, &mut [u8]>,
+        outputs: &mut core::slice::IterMut<'_, &mut [u8]>,
+    ) {
+        for (i, branch) in self.branches.iter().enumerate() {
+            #[cfg_attr(not(feature = "zeroize"), allow(clippy::clone_on_copy))]
+            let mut gamma = sigma.clone();
+            if i > 0 {
+                gamma
+                    .restart(branch.ann(), [])
+                    .expect("lengths are checked");
+            }
+            match branch {
+                Branch::Output(_) => {
+                    let y = outputs.next().expect("number of outputs is checked");
+                    gamma.encr(y).expect("automaton is keyed");
+                }
+                Branch::Child(_, child) => {
+                    let key = child_keys.next().expect("number of keys is checked");
+                    gamma.squeeze(key);
+                    let (l, d) = child.level;
+                    let sigma = BashPrg::start(l, d, child.branches[0].ann(), &**key)
+                        .expect("lengths are checked");
+                    child.derive_node(&sigma, child_keys, outputs);
+                }
+            }
+        }
+    }
+
+    /// Derive child keys and encrypt outputs in place.
+    ///
+    /// # Arguments
+    ///
+    /// * key - key of the root.
+    /// * id - identifier loaded into the root.
+    /// * child_keys - to store child keys, in depth-first order.
+    /// * outputs - data to encrypt, in depth-first order.
+    pub fn derive(
+        &self,
+        key: impl AsRef<[u8]>,
+        id: impl AsRef<[u8]>,
+        child_keys: &mut [&mut [u8]],
+        outputs: &mut [&mut [u8]],
+    ) -> Result<(), LadderError> {
+        let key = key.as_ref();
+        let lens: Vec<usize> = child_keys.iter().map(|k| k.len()).collect();
+        self.check(key.len(), &lens, outputs.len())?;
+
+        let (l, d) = self.level;
+        let mut sigma =
+            BashPrg::start(l, d, self.branches[0].ann(), key).expect("lengths are checked");
+        sigma.absorb(id);
+        sigma.ratchet();
+        self.derive_node(&sigma, &mut child_keys.iter_mut(), &mut outputs.iter_mut());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const K: [u8; 32] = [
+        0xB1, 0x94, 0xBA, 0xC8, 0x0A, 0x08, 0xF5, 0x3B, 0x36, 0x6D, 0x00, 0x8E, 0x58, 0x4A, 0x5D,
+        0xE4, 0x85, 0x04, 0xFA, 0x9D, 0x1B, 0xB6, 0xC7, 0xAC, 0x25, 0x2E, 0x72, 0xC2, 0x02, 0xFD,
+        0xCE, 0x0D,
+    ];
+
+    #[test]
+    fn ladder_test() {
+        let mut k1 = [0u8; 16];
+        let mut y1 = [0u8; 23];
+        let mut y2 = [0u8; 23];
+        crate::prg::programming(K, b"id", &K[..16], &K[16..20], &mut y1, &mut y2, &mut k1).unwrap();
+
+        let mut k1_ = [0u8; 16];
+        let mut y1_ = [0u8; 23];
+        let mut y2_ = [0u8; 23];
+        let mut y3_ = [0u8; 23];
+        KeyLadder::new(256, 2)
+            .child(
+                &[],
+                KeyLadder::new(128, 1)
+                    .branch(&K[..16])
+                    .branch(&K[16..20])
+                    .branch(&K[20..24]),
+            )
+            .derive(
+                K,
+                b"id",
+                &mut [&mut k1_],
+                &mut [&mut y1_, &mut y2_, &mut y3_],
+            )
+            .unwrap();
+        assert_eq!(k1_, k1);
+        assert_eq!(y1_, y1);
+        assert_eq!(y2_, y2);
+        assert_ne!(y3_, y2);
+
+        // Root annotation and levels change the child key.
+        let mut k2 = [0u8; 24];
+        KeyLadder::new(256, 2)
+            .child(b"root", KeyLadder::new(192, 2).branch(&[]))
+            .derive(K, b"id", &mut [&mut k2], &mut [&mut y1_])
+            .unwrap();
+        assert_ne!(k2[..16], k1[..]);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "zeroize"), allow(clippy::clone_on_copy))]
+    fn ladder_test_tree() {
+        // root (256, 2): [y0 "out0", "kid1" -> (192, 1)]
+        // (192, 1): ["kid2" -> (128, 1), y2 "out2"]
+        // (128, 1): [y1 "out1"]
+        let mut keys = [[0u8; 24], [0u8; 24]];
+        let mut ys = [[0u8; 10], [1u8; 10], [2u8; 10]];
+        let ladder = KeyLadder::new(256, 2).branch(b"out0").child(
+            b"kid1",
+            KeyLadder::new(192, 1)
+                .child(b"kid2", KeyLadder::new(128, 1).branch(b"out1"))
+                .branch(b"out2"),
+        );
+        {
+            let [k1, k2] = &mut keys;
+            let [y0, y1, y2] = &mut ys;
+            ladder
+                .derive(K, b"id", &mut [k1, &mut k2[..16]], &mut [y0, y1, y2])
+                .unwrap();
+        }
+
+        let mut root = BashPrg::start(256, 2, b"out0", K).unwrap();
+        root.absorb(b"id");
+        root.ratchet();
+        let mut y0 = [0u8; 10];
+        let mut gamma = root.clone();
+        gamma.encr(&mut y0).unwrap();
+        assert_eq!(ys[0], y0);
+
+        let mut k1 = [0u8; 24];
+        let mut gamma = root.clone();
+        gamma.restart(b"kid1", []).unwrap();
+        gamma.squeeze(&mut k1);
+        assert_eq!(keys[0], k1);
+
+        let node1 = BashPrg::start(192, 1, b"kid2", k1).unwrap();
+        let mut k2 = [0u8; 16];
+        let mut gamma = node1.clone();
+        gamma.squeeze(&mut k2);
+        assert_eq!(keys[1][..16], k2);
+        assert!(keys[1][16..].iter().all(|&x| x == 0));
+
+        let mut y1 = [1u8; 10];
+        let mut gamma = BashPrg::start(128, 1, b"out1", k2).unwrap();
+        gamma.encr(&mut y1).unwrap();
+        assert_eq!(ys[1], y1);
+
+        let mut y2 = [2u8; 10];
+        let mut gamma = node1.clone();
+        gamma.restart(b"out2", []).unwrap();
+        gamma.encr(&mut y2).unwrap();
+        assert_eq!(ys[2], y2);
+    }
+
+    #[test]
+    fn ladder_test_errors() {
+        let mut k1 = [0u8; 16];
+        let mut y = [0u8; 4];
+        let ladder = KeyLadder::programming(&[], &[]);
+        assert_eq!(
+            ladder.derive(&K[..16], [], &mut [&mut k1], &mut [&mut y, &mut []]),
+            Err(LadderError::InvalidKeyLength)
+        );
+        assert_eq!(
+            ladder.derive(K, [], &mut [&mut k1[..12]], &mut [&mut y, &mut []]),
+            Err(LadderError::InvalidChildKeyLength)
+        );
+        assert_eq!(
+            ladder.derive(K, [], &mut [&mut k1], &mut [&mut y]),
+            Err(LadderError::BranchCountMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            ladder.derive(K, [], &mut [], &mut [&mut y, &mut []]),
+            Err(LadderError::ChildKeyCountMismatch {
+                expected: 1,
+                actual: 0
+            })
+        );
+        assert_eq!(
+            KeyLadder::new(256, 2).check(32, &[], 0),
+            Err(LadderError::NoBranches)
+        );
+        assert_eq!(
+            KeyLadder::new(256, 2)
+                .child(&[], KeyLadder::new(128, 1))
+                .check(32, &[16], 0),
+            Err(LadderError::NoBranches)
+        );
+        assert_eq!(
+            KeyLadder::new(160, 2).branch(&[]).check(32, &[], 1),
+            Err(LadderError::InvalidLevel)
+        );
+        assert_eq!(
+            KeyLadder::new(256, 2)
+                .child(&[], KeyLadder::new(128, 3).branch(&[]))
+                .check(32, &[16], 1),
+            Err(LadderError::InvalidLevel)
+        );
+        assert_eq!(
+            KeyLadder::new(128, 1).branch(&K[..3]).check(16, &[], 1),
+            Err(LadderError::InvalidAnnotation)
+        );
+        assert_eq!(
+            KeyLadder::new(128, 1)
+                .child(&[0; 64], KeyLadder::new(128, 1).branch(&[]))
+                .check(16, &[16], 1),
+            Err(LadderError::InvalidAnnotation)
+        );
+
+        // Deep nodes are checked.
+        let deep = KeyLadder::new(128, 1).child(
+            &[],
+            KeyLadder::new(128, 1).child(&[], KeyLadder::new(256, 1).branch(&K[..3])),
+        );
+        assert_eq!(
+            deep.check(16, &[16, 32], 1),
+            Err(LadderError::InvalidAnnotation)
+        );
+        let deep = KeyLadder::new(128, 1).child(
+            &[],
+            KeyLadder::new(128, 1).child(&[], KeyLadder::new(256, 1).branch(&[])),
+        );
+        assert_eq!(
+            deep.check(16, &[16, 16], 1),
+            Err(LadderError::InvalidChildKeyLength)
+        );
+        assert_eq!(deep.check(16, &[16, 32], 1), Ok(()));
+
+        // Nothing is written on error.
+        assert!(k1.iter().all(|&x| x == 0));
+        assert!(y.iter().all(|&x| x == 0));
+
+        // `programming()` keeps its error.
+        assert!(matches!(
+            crate::prg::programming(&K[..16], [], [], [], &mut y, &mut [], &mut k1),
+            Err(bee2_core::error::Error::InvalidLength(_))
+        ));
+    }
+}
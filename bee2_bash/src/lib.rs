@@ -1,11 +1,42 @@
+pub mod checkpoint;
+#[cfg(feature = "std")]
+pub mod chunked;
 mod consts;
 mod hash;
+pub mod kdf;
+pub mod ladder;
+mod multi;
 mod prg;
+pub mod session;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod simd;
+#[cfg(feature = "std")]
+mod stream;
+pub mod tree;
+pub mod typed;
 
-pub use crate::consts::bash_f0;
-pub use crate::hash::{Bash256, Bash384, Bash512};
+#[cfg(feature = "std")]
+pub use crate::chunked::{BashPrgStreamDecoder, BashPrgStreamEncoder};
+pub use crate::consts::{bash_f0, BashFBackend};
+pub use crate::hash::{Bash256, Bash384, Bash512, BashHash};
+pub use crate::kdf::BashPrgKdf;
+pub use crate::ladder::KeyLadder;
 pub use crate::prg::{
-    programming, BashPrgAEAD2561, BashPrgAEAD2562, BashPrgAEAD3841, BashPrgAEAD3842,
+    programming, BashPrg, BashPrgAEAD2561, BashPrgAEAD2562, BashPrgAEAD3841, BashPrgAEAD3842,
     BashPrgAEAD5121, BashPrgAEAD5122, BashPrgHash2561, BashPrgHash2562, BashPrgHash3841,
-    BashPrgHash3842, BashPrgHash5121, BashPrgHash5122,
+    BashPrgHash3842, BashPrgHash5121, BashPrgHash5122, BashPrgHashReader, BashPrgMac2561,
+    BashPrgMac2562, BashPrgMac3841, BashPrgMac3842, BashPrgMac5121, BashPrgMac5122,
 };
+#[cfg(feature = "rust-crypto")]
+pub use crate::prg::{
+    BashPrgAEADCipher2561, BashPrgAEADCipher2562, BashPrgAEADCipher3841, BashPrgAEADCipher3842,
+    BashPrgAEADCipher5121, BashPrgAEADCipher5122,
+};
+pub use crate::session::BashPrgSession;
+#[cfg(feature = "std")]
+pub use crate::stream::{BashPrgReader, BashPrgWriter};
+pub use crate::typed::TypedBashPrg;
+#[cfg(feature = "rust-crypto")]
+pub use aead;
+#[cfg(feature = "rust-crypto")]
+pub use digest;
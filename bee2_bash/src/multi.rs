@@ -0,0 +1,185 @@
+//! Hashing of many independent messages by `bash-f` on several states at once.
+
+use crate::consts::bash_f0;
+use crate::hash::{Bash256, Bash384, Bash512};
+
+/// Number of states processed by one call of `bash-f`.
+const LANES: usize = 4;
+
+/// `bash-f` on `LANES` states, word `w` of state `k` is `s[LANES * w + k]`.
+fn bash_f0_lanes(s: &mut [u64; 24 * LANES]) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            return unsafe { crate::simd::avx2_x4::bash_f0(s) };
+        }
+    }
+    for k in 0..LANES {
+        let mut state = [0u64; 24];
+        for (w, x) in state.iter_mut().enumerate() {
+            *x = s[LANES * w + k];
+        }
+        bash_f0(&mut state);
+        for (w, x) in state.iter().enumerate() {
+            s[LANES * w + k] = *x;
+        }
+    }
+}
+
+/// Message in a lane.
+#[derive(Copy, Clone)]
+struct Job {
+    /// Index of message.
+    index: usize,
+    /// Length of processed part.
+    pos: usize,
+}
+
+/// Hash `srcs` into `hashes` as `Bash::hash(l, ...)` does.
+fn hash_many<S: AsRef<[u8]>>(l: usize, hashes: &mut [&mut [u8]], srcs: &[S]) {
+    assert_eq!(hashes.len(), srcs.len(), "one hash per message");
+    let buff_len = 192 - l / 2;
+    let mut s = [0u64; 24 * LANES];
+    let mut lanes: [Option<Job>; LANES] = [None; LANES];
+    let mut next = 0;
+
+    loop {
+        let mut last = [false; LANES];
+        for (k, lane) in lanes.iter_mut().enumerate() {
+            if lane.is_none() && next < srcs.len() {
+                // Bash::bash_start(l).
+                for w in 0..24 {
+                    s[LANES * w + k] = 0;
+                }
+                s[LANES * 23 + k] = (l / 4) as u64;
+                *lane = Some(Job {
+                    index: next,
+                    pos: 0,
+                });
+                next += 1;
+            }
+            let job = match lane {
+                Some(job) => job,
+                None => continue,
+            };
+
+            // Block of message or the last block padded by 0x40 || 0...
+            let src = &srcs[job.index].as_ref()[job.pos..];
+            let mut block = [0u8; 192];
+            let count = src.len().min(buff_len);
+            block[..count].copy_from_slice(&src[..count]);
+            if count < buff_len {
+                block[count] = 0x40;
+                last[k] = true;
+            }
+            job.pos += count;
+            for (w, word) in block[..buff_len].chunks_exact(8).enumerate() {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(word);
+                s[LANES * w + k] = u64::from_le_bytes(bytes);
+            }
+        }
+        if lanes.iter().all(Option::is_none) {
+            break;
+        }
+
+        bash_f0_lanes(&mut s);
+
+        for k in (0..LANES).filter(|&k| last[k]) {
+            if let Some(job) = lanes[k].take() {
+                let hash = &mut hashes[job.index];
+                for (w, out) in hash.chunks_mut(8).enumerate() {
+                    out.copy_from_slice(&s[LANES * w + k].to_le_bytes()[..out.len()]);
+                }
+            }
+        }
+    }
+}
+
+macro_rules! bash_many {
+    ($bash_x: ty, $l: expr) => {
+        impl $bash_x {
+            /// Hashing of every message of `srcs` into the corresponding element of `hashes`.
+            ///
+            /// Messages are processed four at a time, `bash-f` is applied to their states at once.
+            ///
+            /// # Panics
+            /// `hashes.len() != srcs.len()`
+            pub fn hash_many<S: AsRef<[u8]>>(hashes: &mut [[u8; $l / 4]], srcs: &[S]) {
+                let mut hashes: Vec<&mut [u8]> = hashes.iter_mut().map(|h| &mut h[..]).collect();
+                hash_many($l, &mut hashes, srcs);
+            }
+        }
+    };
+}
+bash_many!(Bash256, 128);
+bash_many!(Bash384, 192);
+bash_many!(Bash512, 256);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hash::Hasher;
+
+    #[test]
+    fn hash_many_test() {
+        let data: Vec<u8> = (0..1000).map(|i| (i * 7 + 3) as u8).collect();
+        // Lengths around block boundaries in different lanes.
+        let lens = [
+            0, 1, 63, 64, 65, 95, 96, 97, 127, 128, 129, 150, 255, 256, 257, 500, 1000, 3,
+        ];
+        let srcs: Vec<&[u8]> = lens.iter().map(|&len| &data[..len]).collect();
+
+        let mut hashes = vec![[0u8; 32]; srcs.len()];
+        Bash256::hash_many(&mut hashes, &srcs);
+        for (hash, src) in hashes.iter().zip(srcs.iter()) {
+            let mut expected = [0u8; 32];
+            Bash256::hash(&mut expected, src);
+            assert_eq!(*hash, expected);
+        }
+
+        let mut hashes = vec![[0u8; 48]; srcs.len()];
+        Bash384::hash_many(&mut hashes, &srcs);
+        for (hash, src) in hashes.iter().zip(srcs.iter()) {
+            let mut expected = [0u8; 48];
+            Bash384::hash(&mut expected, src);
+            assert_eq!(*hash, expected);
+        }
+
+        let mut hashes = vec![[0u8; 64]; srcs.len()];
+        Bash512::hash_many(&mut hashes, &srcs);
+        for (hash, src) in hashes.iter().zip(srcs.iter()) {
+            let mut expected = [0u8; 64];
+            Bash512::hash(&mut expected, src);
+            assert_eq!(*hash, expected);
+        }
+
+        let mut hashes: [[u8; 32]; 0] = [];
+        Bash256::hash_many(&mut hashes, &[] as &[&[u8]]);
+    }
+
+    #[test]
+    fn bash_f0_lanes_test() {
+        let mut s = [0u64; 24 * LANES];
+        let mut x = 0x0123456789ABCDEFu64;
+        for w in s.iter_mut() {
+            x = x
+                .wrapping_mul(0x5851F42D4C957F2D)
+                .wrapping_add(0x14057B7EF767814F);
+            *w = x;
+        }
+        let mut expected = s;
+        for k in 0..LANES {
+            let mut state = [0u64; 24];
+            for (w, x) in state.iter_mut().enumerate() {
+                *x = expected[LANES * w + k];
+            }
+            bash_f0(&mut state);
+            for (w, x) in state.iter().enumerate() {
+                expected[LANES * w + k] = *x;
+            }
+        }
+        bash_f0_lanes(&mut s);
+        assert_eq!(s[..], expected[..]);
+    }
+}
@@ -1,7 +1,12 @@
-pub use bee2_core::error::{Error, IncorrectTag, InvalidCommand, InvalidLength};
+pub use bee2_core::error::{Error, IncorrectTag, InvalidCommand, InvalidLength, StateError};
+use bee2_core::mem::mem_eq;
 pub use bee2_traits::*;
 
-use crate::consts::bash_f;
+use crate::checkpoint::{
+    self, Header, FLAG_ABSORBING, FLAG_KEY, KIND_PRG, KIND_PRG_HASH, STATE_LEN,
+};
+use crate::consts::{bash_f, State};
+use crate::ladder::KeyLadder;
 
 #[allow(non_camel_case_types)]
 #[repr(u8)]
@@ -13,23 +18,28 @@ pub enum PrgCommands {
     BASH_PRG_OUT = 0x11,  /* 000100 01 */
 }
 
-#[derive(Copy, Clone)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[derive(Clone)]
 struct BashPrgState {
     /// Security level.
     l: usize,
     /// Capacity level.
     d: usize,
     /// Current state.
-    s: [u8; 192],
+    s: State,
     /// Copy of s1.
-    t: [u8; 192],
+    t: State,
     /// Size(length) of buffer.
     buff_len: usize,
     /// Current position in buffer.
     pos: usize,
 }
 
-#[derive(Clone, Copy)]
+/// Automaton of STB 34.101.77, section 8.
+///
+/// The automaton is `Copy` unless feature `zeroize` is enabled, then its state is wiped on drop.
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[derive(Clone)]
 pub struct BashPrg {
     /// State.
     state: BashPrgState,
@@ -43,6 +53,11 @@ impl BashPrgState {
 }
 
 impl BashPrg {
+    /// Whether the automaton is started with key.
+    pub(crate) fn is_key_mode(&self) -> bool {
+        self.state.is_key_mode()
+    }
+
     /// `Commit` command.
     /// Finish previous command and start new one using `code`.
     fn prg_commit(&mut self, code: u8) {
@@ -53,6 +68,64 @@ impl BashPrg {
     }
 }
 
+impl BashPrg {
+    fn export_kind(&self, kind: u8, flags: u8) -> [u8; STATE_LEN] {
+        let header = Header {
+            kind,
+            l: self.state.l,
+            d: self.state.d,
+            flags: flags | if self.is_key_mode() { FLAG_KEY } else { 0 },
+            pos: self.state.pos,
+        };
+        checkpoint::encode(&header, &self.state.s)
+    }
+
+    fn import_kind(state: &[u8], kind: u8) -> Result<(Self, u8), StateError> {
+        let (header, s) = checkpoint::decode(state, kind)?;
+        let (l, d) = (header.l, header.d);
+        if (l != 128 && l != 192 && l != 256) || (d != 1 && d != 2) {
+            return Err(StateError::InvalidFormat);
+        }
+        let buff_len = match header.flags & FLAG_KEY {
+            0 => 192 - d * l / 4,
+            _ => 192 - l * (2 + d) / 16,
+        };
+        if header.pos >= buff_len {
+            return Err(StateError::InvalidFormat);
+        }
+        let prg = BashPrg {
+            state: BashPrgState {
+                l,
+                d,
+                s,
+                t: State::default(),
+                buff_len,
+                pos: header.pos,
+            },
+        };
+        Ok((prg, header.flags & !FLAG_KEY))
+    }
+
+    /// Export state of the automaton, see `checkpoint`.
+    ///
+    /// The state of the automaton in key mode is as secret as its key.
+    pub fn export_state(&self) -> [u8; STATE_LEN] {
+        self.export_kind(KIND_PRG, 0)
+    }
+
+    /// Import state exported by `BashPrg::export_state()`.
+    ///
+    /// # Arguments
+    ///
+    /// * state - exported state, state.len() == STATE_LEN
+    pub fn import_state(state: impl AsRef<[u8]>) -> Result<Self, StateError> {
+        match Self::import_kind(state.as_ref(), KIND_PRG)? {
+            (prg, 0) => Ok(prg),
+            _ => Err(StateError::InvalidFormat),
+        }
+    }
+}
+
 impl PrgStart for BashPrg {
     fn start(
         l: usize,
@@ -85,7 +158,7 @@ impl PrgStart for BashPrg {
 
         // pos <- 8 + |ann| + |key|
         let pos = 1 + ann.len() + key.len();
-        let mut s = [0; 192];
+        let mut s = State::default();
         // s[0..pos) <- <|ann|/2 + |key|/32>_8 || ann || key
         s[0] = (ann.len() * 4 + key.len() / 4) as u8;
         s[1..1 + ann.len()].copy_from_slice(ann);
@@ -100,10 +173,10 @@ impl PrgStart for BashPrg {
                 d,
                 pos,
                 s,
-                t: [0; 192],
+                t: State::default(),
                 buff_len: match key.len() {
-                    0 => (192 - d * l / 4),
-                    _ => (192 - l * (2 + d) / 16),
+                    0 => 192 - d * l / 4,
+                    _ => 192 - l * (2 + d) / 16,
                 },
             },
         })
@@ -199,7 +272,7 @@ impl PrgAbsorb for BashPrg {
 
         while count >= self.state.buff_len {
             mem_xor(
-                &mut self.state.s,
+                &mut self.state.s[..],
                 &buf[copy_size..copy_size + self.state.buff_len],
             );
             copy_size += self.state.buff_len;
@@ -309,7 +382,7 @@ impl PrgEncr for BashPrg {
 
         while count >= self.state.buff_len {
             mem_xor(
-                &mut self.state.s,
+                &mut self.state.s[..],
                 &buf[copy_size..copy_size + self.state.buff_len],
             );
             mem_cpy(
@@ -384,7 +457,7 @@ impl PrgDecr for BashPrg {
                 &self.state.s[0..self.state.buff_len],
             );
             mem_xor(
-                &mut self.state.s,
+                &mut self.state.s[..],
                 &buf[copy_size..copy_size + self.state.buff_len],
             );
             copy_size += self.state.buff_len;
@@ -415,9 +488,11 @@ impl PrgDecr for BashPrg {
 
 impl PrgRatchet for BashPrg {
     fn ratchet(&mut self) {
-        mem_cpy(&mut self.state.t, &self.state.s);
+        mem_cpy(&mut self.state.t[..], &self.state.s[..]);
         self.prg_commit(PrgCommands::BASH_PRG_NULL as u8);
-        mem_xor(&mut self.state.s, &self.state.t);
+        mem_xor(&mut self.state.s[..], &self.state.t[..]);
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut self.state.t[..]);
     }
 }
 
@@ -428,6 +503,18 @@ impl Prg for BashPrg {
 }
 
 /// Block 8.11 Programming
+///
+/// Key ladder `(256, 2)` -> `(128, 1)`, see `KeyLadder::programming()`.
+///
+/// # Arguments
+///
+/// * k - key, k.len() % 4 == 0 && 32 <= k.len() <= 60
+/// * i - identifier.
+/// * a1, a2 - annotations, a.len() % 4 == 0 && a.len() <= 60
+/// * y1, y2 - data to encrypt in place.
+/// * k1 - to store child key, k1.len() % 4 == 0 && 16 <= k1.len() <= 60
+///
+/// Every error of the ladder is reported as `Error::InvalidLength`.
 pub fn programming(
     k: impl AsRef<[u8]>,
     i: impl AsRef<[u8]>,
@@ -437,50 +524,96 @@ pub fn programming(
     y2: &mut [u8],
     k1: &mut [u8],
 ) -> Result<(), Error> {
-    // Step 1.
-    let mut alpha = BashPrg::start(256, 2, [], k)?;
-    // Step 2.
-    alpha.absorb(i);
-    // Step 3.
-    alpha.ratchet();
-    // Step 4.
-    alpha.squeeze(k1);
-    // Step 5.
-    let mut beta = BashPrg::start(128, 1, a1, k1)?;
-    // Step 6.
-    let mut gamma = beta;
-    // Step 7.
-    gamma.restart(a2, [])?;
-    // Step 8.
-    beta.encr(y1)?;
-    // Step 9.
-    gamma.encr(y2)?;
+    KeyLadder::programming(a1.as_ref(), a2.as_ref())
+        .derive(k, i, &mut [k1], &mut [y1, y2])
+        .map_err(|_| InvalidLength)?;
 
     Ok(())
 }
 
+/// Output of `bash-prg-hash` of arbitrary length.
+#[derive(Clone)]
+pub struct BashPrgHashReader {
+    prg: BashPrg,
+}
+
+impl BashPrgHashReader {
+    /// Unload next part of hash-value.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - to store output, consecutive calls continue the output.
+    pub fn read(&mut self, buf: &mut [u8]) {
+        self.prg.squeeze_step(buf);
+    }
+}
+
 macro_rules! bash_prg_hash {
     ($full_name:ident, $security_level:expr, $capacity:expr) => {
+        #[doc = "Hashing `bash-prg-hash`: `start(l, d, ann, [])`, `absorb(data)`, `squeeze(hash)`."]
+        #[doc = ""]
+        #[doc = concat!("l = ", stringify!($security_level), ", d = ", stringify!($capacity), ".")]
         #[derive(Clone)]
         pub struct $full_name {
             prg: BashPrg,
+            /// Whether command `absorb` is started.
+            absorbing: bool,
+        }
+
+        impl $full_name {
+            /// Export state to continue hashing later, see `checkpoint`.
+            pub fn export_state(&self) -> [u8; STATE_LEN] {
+                let flags = if self.absorbing { FLAG_ABSORBING } else { 0 };
+                self.prg.export_kind(KIND_PRG_HASH, flags)
+            }
+
+            /// Import state exported by `export_state()` of the same type.
+            ///
+            /// # Arguments
+            ///
+            /// * state - exported state, state.len() == STATE_LEN
+            pub fn import_state(state: impl AsRef<[u8]>) -> Result<Self, StateError> {
+                let (prg, flags) = BashPrg::import_kind(state.as_ref(), KIND_PRG_HASH)?;
+                if prg.state.l != $security_level || prg.state.d != $capacity || prg.is_key_mode() {
+                    return Err(StateError::TypeMismatch);
+                }
+                Ok(Self {
+                    prg,
+                    absorbing: flags & FLAG_ABSORBING != 0,
+                })
+            }
+
+            /// Finish loading of data and get hash-value of arbitrary length.
+            pub fn finalize_xof(mut self) -> BashPrgHashReader {
+                if !self.absorbing {
+                    self.prg.absorb_start();
+                }
+                self.prg.squeeze_start();
+                BashPrgHashReader { prg: self.prg }
+            }
         }
 
         impl PrgHasher for $full_name {
             fn new(ann: impl AsRef<[u8]>) -> Result<Self, InvalidLength> {
                 Ok(Self {
                     prg: BashPrg::start($security_level, $capacity, ann, [])?,
+                    absorbing: false,
                 })
             }
 
             fn update(&mut self, data: impl AsRef<[u8]>) {
-                self.prg.absorb(data);
+                if !self.absorbing {
+                    self.prg.absorb_start();
+                    self.absorbing = true;
+                }
+                self.prg.absorb_step(data);
             }
 
             fn hash(&mut self, data: impl AsRef<[u8]>, hash: &mut [u8]) {
-                self.prg.absorb(data);
+                self.update(data);
                 self.prg.squeeze(hash);
                 self.prg.ratchet();
+                self.absorbing = false;
             }
         }
     };
@@ -493,8 +626,28 @@ bash_prg_hash!(BashPrgHash3842, 192, 2);
 bash_prg_hash!(BashPrgHash5121, 256, 1);
 bash_prg_hash!(BashPrgHash5122, 256, 2);
 
+/// Squeeze `tag.len()` octets and compare them with `tag` in constant time.
+///
+/// Tags shorter than l / 8 are rejected since a prefix of the tag would otherwise match.
+pub(crate) fn squeeze_eq(prg: &mut BashPrg, tag: &[u8]) -> bool {
+    let mut buf = [0u8; 32];
+    let mut eq = tag.len() >= prg.state.l / 8;
+    prg.squeeze_start();
+    for chunk in tag.chunks(buf.len()) {
+        let buf = &mut buf[..chunk.len()];
+        prg.squeeze_step(buf);
+        eq &= mem_eq(buf, chunk);
+    }
+    eq
+}
+
 macro_rules! bash_prg_aead {
     ($full_name:ident, $security_level:expr, $capacity:expr) => {
+        #[doc = "Authenticated encryption `bash-prg-ae`."]
+        #[doc = ""]
+        #[doc = "`start(l, d, ann, key)`, `absorb(header)`, `encr(data)`, `squeeze(tag)`."]
+        #[doc = ""]
+        #[doc = concat!("l = ", stringify!($security_level), ", d = ", stringify!($capacity), ".")]
         #[derive(Clone)]
         pub struct $full_name {
             prg: BashPrg,
@@ -507,36 +660,35 @@ macro_rules! bash_prg_aead {
                 })
             }
 
-            fn encrypt(
+            fn encrypt_in_place_detached(
                 &mut self,
-                plaintext: impl AsRef<[u8]>,
                 header: impl AsRef<[u8]>,
-                ciphertext: &mut [u8],
+                buffer: &mut [u8],
                 tag: &mut [u8],
             ) -> Result<(), Error> {
+                if tag.len() < $security_level / 8 {
+                    return Err(Error::from(InvalidLength));
+                }
                 self.prg.absorb(header);
-                ciphertext.clone_from_slice(&plaintext.as_ref());
-                self.prg.encr(ciphertext)?;
+                self.prg.encr(buffer)?;
                 self.prg.squeeze(tag);
 
                 Ok(())
             }
 
-            fn decrypt(
+            fn decrypt_in_place_detached(
                 &mut self,
-                ciphertext: impl AsRef<[u8]>,
                 header: impl AsRef<[u8]>,
-                tag_: impl AsRef<[u8]>,
-                plaintext: &mut [u8],
+                buffer: &mut [u8],
+                tag: impl AsRef<[u8]>,
             ) -> Result<(), Error> {
+                if tag.as_ref().len() < $security_level / 8 {
+                    return Err(Error::from(InvalidLength));
+                }
                 self.prg.absorb(header);
-                plaintext.clone_from_slice(&ciphertext.as_ref());
-                self.prg.decr(plaintext)?;
-                let tag = tag_.as_ref();
-                let mut tag_get: Box<[u8]> = vec![0; tag.len()].into_boxed_slice();
-                self.prg.squeeze(tag_get.as_mut());
-                if tag_get.as_ref().eq(tag) == false {
-                    plaintext.iter_mut().for_each(|x| *x = 0);
+                self.prg.decr(buffer)?;
+                if !squeeze_eq(&mut self.prg, tag.as_ref()) {
+                    buffer.iter_mut().for_each(|x| *x = 0);
 
                     // panic!(format!("Incorrect tag").to_owned());
                     return Err(Error::from(IncorrectTag));
@@ -555,6 +707,392 @@ bash_prg_aead!(BashPrgAEAD3842, 192, 2);
 bash_prg_aead!(BashPrgAEAD5121, 256, 1);
 bash_prg_aead!(BashPrgAEAD5122, 256, 2);
 
+macro_rules! bash_prg_mac {
+    ($full_name:ident, $security_level:expr, $capacity:expr) => {
+        #[doc = "Keyed hashing: `start(l, d, ann, key)`, `absorb(data)`, `squeeze(tag)`."]
+        #[doc = ""]
+        #[doc = "The automaton is ratcheted after every tag, so consecutive messages are"]
+        #[doc = "authenticated in a chain."]
+        #[doc = ""]
+        #[doc = concat!("l = ", stringify!($security_level), ", d = ", stringify!($capacity), ".")]
+        #[derive(Clone)]
+        pub struct $full_name {
+            prg: BashPrg,
+            /// Whether command `absorb` is started.
+            absorbing: bool,
+        }
+
+        impl PrgMac for $full_name {
+            fn new(ann: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> Result<Self, InvalidLength> {
+                if key.as_ref().is_empty() {
+                    return Err(InvalidLength);
+                }
+                Ok(Self {
+                    prg: BashPrg::start($security_level, $capacity, ann, key)?,
+                    absorbing: false,
+                })
+            }
+
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                if !self.absorbing {
+                    self.prg.absorb_start();
+                    self.absorbing = true;
+                }
+                self.prg.absorb_step(data);
+            }
+
+            fn mac(&mut self, data: impl AsRef<[u8]>, tag: &mut [u8]) {
+                self.update(data);
+                self.prg.squeeze(tag);
+                self.prg.ratchet();
+                self.absorbing = false;
+            }
+
+            fn verify(
+                &mut self,
+                data: impl AsRef<[u8]>,
+                tag: impl AsRef<[u8]>,
+            ) -> Result<(), IncorrectTag> {
+                self.update(data);
+                let eq = squeeze_eq(&mut self.prg, tag.as_ref());
+                self.prg.ratchet();
+                self.absorbing = false;
+                if !eq {
+                    return Err(IncorrectTag);
+                }
+
+                Ok(())
+            }
+
+            fn output_size() -> usize {
+                $security_level / 8
+            }
+        }
+    };
+}
+
+bash_prg_mac!(BashPrgMac2561, 128, 1);
+bash_prg_mac!(BashPrgMac2562, 128, 2);
+bash_prg_mac!(BashPrgMac3841, 192, 1);
+bash_prg_mac!(BashPrgMac3842, 192, 2);
+bash_prg_mac!(BashPrgMac5121, 256, 1);
+bash_prg_mac!(BashPrgMac5122, 256, 2);
+
+#[cfg(feature = "rust-crypto")]
+pub use crypto::{
+    BashPrgAEADCipher2561, BashPrgAEADCipher2562, BashPrgAEADCipher3841, BashPrgAEADCipher3842,
+    BashPrgAEADCipher5121, BashPrgAEADCipher5122,
+};
+
+#[cfg(feature = "rust-crypto")]
+mod crypto {
+    use aead::consts::{U0, U16, U24, U32};
+    use aead::{AeadCore, AeadInPlace, Key, KeyInit, KeySizeUser, Nonce, Tag};
+    use digest::{
+        ExtendableOutput, FixedOutput, MacMarker, Output, OutputSizeUser, Update, XofReader,
+    };
+
+    use super::*;
+
+    impl XofReader for BashPrgHashReader {
+        fn read(&mut self, buffer: &mut [u8]) {
+            self.prg.squeeze_step(buffer);
+        }
+    }
+
+    macro_rules! bash_prg_crypto {
+        ($full_name:ident) => {
+            impl Default for $full_name {
+                fn default() -> Self {
+                    <$full_name as PrgHasher>::new([]).unwrap()
+                }
+            }
+
+            impl Update for $full_name {
+                fn update(&mut self, data: &[u8]) {
+                    PrgHasher::update(self, data);
+                }
+            }
+
+            impl ExtendableOutput for $full_name {
+                type Reader = BashPrgHashReader;
+
+                fn finalize_xof(self) -> Self::Reader {
+                    $full_name::finalize_xof(self)
+                }
+            }
+        };
+    }
+
+    bash_prg_crypto!(BashPrgHash2561);
+    bash_prg_crypto!(BashPrgHash2562);
+    bash_prg_crypto!(BashPrgHash3841);
+    bash_prg_crypto!(BashPrgHash3842);
+    bash_prg_crypto!(BashPrgHash5121);
+    bash_prg_crypto!(BashPrgHash5122);
+
+    macro_rules! bash_prg_aead_crypto {
+        ($full_name:ident, $aead:ident, $security_level:expr, $capacity:expr, $size:ty) => {
+            #[doc = concat!("`aead` interface over [`", stringify!($aead), "`].")]
+            #[doc = ""]
+            #[doc = "Every message is processed by a new automaton `start(l, d, nonce, key)`,"]
+            #[doc = "so the nonce takes the place of the annotation and"]
+            #[doc = "`KeyInit::new(key).encrypt(nonce, ...)` gives the same result as"]
+            #[doc = concat!("`", stringify!($aead), "::new(nonce, key).encrypt(...)`.")]
+            #[doc = "The key is l / 8 octets, the nonce is 16 octets and the tag is l / 8 octets"]
+            #[doc = "appended to the ciphertext."]
+            #[derive(Clone)]
+            pub struct $full_name {
+                pub(super) key: Key<Self>,
+            }
+
+            impl KeySizeUser for $full_name {
+                type KeySize = $size;
+            }
+
+            impl KeyInit for $full_name {
+                fn new(key: &Key<Self>) -> Self {
+                    Self { key: key.clone() }
+                }
+            }
+
+            impl AeadCore for $full_name {
+                type NonceSize = U16;
+                type TagSize = $size;
+                type CiphertextOverhead = U0;
+            }
+
+            impl $full_name {
+                fn start_nonce(&self, nonce: &Nonce<Self>) -> BashPrg {
+                    BashPrg::start($security_level, $capacity, nonce, self.key)
+                        .expect("lengths are fixed by types")
+                }
+            }
+
+            impl AeadInPlace for $full_name {
+                fn encrypt_in_place_detached(
+                    &self,
+                    nonce: &Nonce<Self>,
+                    associated_data: &[u8],
+                    buffer: &mut [u8],
+                ) -> aead::Result<Tag<Self>> {
+                    let mut prg = self.start_nonce(nonce);
+                    prg.absorb(associated_data);
+                    prg.encr(buffer).map_err(|_| aead::Error)?;
+                    let mut tag = Tag::<Self>::default();
+                    prg.squeeze(&mut tag);
+
+                    Ok(tag)
+                }
+
+                fn decrypt_in_place_detached(
+                    &self,
+                    nonce: &Nonce<Self>,
+                    associated_data: &[u8],
+                    buffer: &mut [u8],
+                    tag: &Tag<Self>,
+                ) -> aead::Result<()> {
+                    let mut prg = self.start_nonce(nonce);
+                    prg.absorb(associated_data);
+                    prg.decr(buffer).map_err(|_| aead::Error)?;
+                    if !squeeze_eq(&mut prg, tag) {
+                        buffer.iter_mut().for_each(|x| *x = 0);
+                        return Err(aead::Error);
+                    }
+
+                    Ok(())
+                }
+            }
+        };
+    }
+
+    bash_prg_aead_crypto!(BashPrgAEADCipher2561, BashPrgAEAD2561, 128, 1, U16);
+    bash_prg_aead_crypto!(BashPrgAEADCipher2562, BashPrgAEAD2562, 128, 2, U16);
+    bash_prg_aead_crypto!(BashPrgAEADCipher3841, BashPrgAEAD3841, 192, 1, U24);
+    bash_prg_aead_crypto!(BashPrgAEADCipher3842, BashPrgAEAD3842, 192, 2, U24);
+    bash_prg_aead_crypto!(BashPrgAEADCipher5121, BashPrgAEAD5121, 256, 1, U32);
+    bash_prg_aead_crypto!(BashPrgAEADCipher5122, BashPrgAEAD5122, 256, 2, U32);
+
+    /// `digest::Mac` interface over `PrgMac` with empty annotation.
+    ///
+    /// The key is l / 8 octets, the tag is l / 8 octets.
+    macro_rules! bash_prg_mac_crypto {
+        ($full_name:ident, $size:ty) => {
+            impl KeySizeUser for $full_name {
+                type KeySize = $size;
+            }
+
+            impl KeyInit for $full_name {
+                fn new(key: &Key<Self>) -> Self {
+                    <$full_name as PrgMac>::new([], key).unwrap()
+                }
+            }
+
+            impl OutputSizeUser for $full_name {
+                type OutputSize = $size;
+            }
+
+            impl Update for $full_name {
+                fn update(&mut self, data: &[u8]) {
+                    PrgMac::update(self, data);
+                }
+            }
+
+            impl FixedOutput for $full_name {
+                fn finalize_into(mut self, out: &mut Output<Self>) {
+                    PrgMac::mac(&mut self, [], out);
+                }
+            }
+
+            impl MacMarker for $full_name {}
+        };
+    }
+
+    bash_prg_mac_crypto!(BashPrgMac2561, U16);
+    bash_prg_mac_crypto!(BashPrgMac2562, U16);
+    bash_prg_mac_crypto!(BashPrgMac3841, U24);
+    bash_prg_mac_crypto!(BashPrgMac3842, U24);
+    bash_prg_mac_crypto!(BashPrgMac5121, U32);
+    bash_prg_mac_crypto!(BashPrgMac5122, U32);
+}
+
+#[cfg(feature = "std")]
+mod io {
+    use std::io::{Read, Result, Write};
+
+    use super::*;
+
+    impl Read for BashPrgHashReader {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.prg.squeeze_step(buf);
+            Ok(buf.len())
+        }
+    }
+
+    macro_rules! bash_prg_io {
+        ($full_name:ident) => {
+            impl Write for $full_name {
+                fn write(&mut self, buf: &[u8]) -> Result<usize> {
+                    self.update(buf);
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> Result<()> {
+                    Ok(())
+                }
+            }
+        };
+    }
+
+    bash_prg_io!(BashPrgHash2561);
+    bash_prg_io!(BashPrgHash2562);
+    bash_prg_io!(BashPrgHash3841);
+    bash_prg_io!(BashPrgHash3842);
+    bash_prg_io!(BashPrgHash5121);
+    bash_prg_io!(BashPrgHash5122);
+    bash_prg_io!(BashPrgMac2561);
+    bash_prg_io!(BashPrgMac2562);
+    bash_prg_io!(BashPrgMac3841);
+    bash_prg_io!(BashPrgMac3842);
+    bash_prg_io!(BashPrgMac5121);
+    bash_prg_io!(BashPrgMac5122);
+}
+
+#[cfg(feature = "zeroize")]
+mod wipe {
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+
+    use super::*;
+
+    impl Zeroize for BashPrg {
+        fn zeroize(&mut self) {
+            self.state.s.zeroize();
+            self.state.t.zeroize();
+            self.state.pos.zeroize();
+        }
+    }
+
+    impl Drop for BashPrg {
+        fn drop(&mut self) {
+            self.zeroize();
+        }
+    }
+
+    impl ZeroizeOnDrop for BashPrg {}
+
+    impl Zeroize for BashPrgHashReader {
+        fn zeroize(&mut self) {
+            self.prg.zeroize();
+        }
+    }
+
+    impl ZeroizeOnDrop for BashPrgHashReader {}
+
+    macro_rules! bash_prg_wipe {
+        ($full_name:ident) => {
+            impl Zeroize for $full_name {
+                fn zeroize(&mut self) {
+                    self.prg.zeroize();
+                }
+            }
+
+            impl ZeroizeOnDrop for $full_name {}
+        };
+    }
+
+    bash_prg_wipe!(BashPrgHash2561);
+    bash_prg_wipe!(BashPrgHash2562);
+    bash_prg_wipe!(BashPrgHash3841);
+    bash_prg_wipe!(BashPrgHash3842);
+    bash_prg_wipe!(BashPrgHash5121);
+    bash_prg_wipe!(BashPrgHash5122);
+    bash_prg_wipe!(BashPrgMac2561);
+    bash_prg_wipe!(BashPrgMac2562);
+    bash_prg_wipe!(BashPrgMac3841);
+    bash_prg_wipe!(BashPrgMac3842);
+    bash_prg_wipe!(BashPrgMac5121);
+    bash_prg_wipe!(BashPrgMac5122);
+
+    bash_prg_wipe!(BashPrgAEAD2561);
+    bash_prg_wipe!(BashPrgAEAD2562);
+    bash_prg_wipe!(BashPrgAEAD3841);
+    bash_prg_wipe!(BashPrgAEAD3842);
+    bash_prg_wipe!(BashPrgAEAD5121);
+    bash_prg_wipe!(BashPrgAEAD5122);
+
+    #[cfg(feature = "rust-crypto")]
+    macro_rules! bash_prg_aead_crypto_wipe {
+        ($full_name:ident) => {
+            impl Zeroize for $full_name {
+                fn zeroize(&mut self) {
+                    self.key.as_mut_slice().zeroize();
+                }
+            }
+
+            impl Drop for $full_name {
+                fn drop(&mut self) {
+                    self.zeroize();
+                }
+            }
+
+            impl ZeroizeOnDrop for $full_name {}
+        };
+    }
+
+    #[cfg(feature = "rust-crypto")]
+    bash_prg_aead_crypto_wipe!(BashPrgAEADCipher2561);
+    #[cfg(feature = "rust-crypto")]
+    bash_prg_aead_crypto_wipe!(BashPrgAEADCipher2562);
+    #[cfg(feature = "rust-crypto")]
+    bash_prg_aead_crypto_wipe!(BashPrgAEADCipher3841);
+    #[cfg(feature = "rust-crypto")]
+    bash_prg_aead_crypto_wipe!(BashPrgAEADCipher3842);
+    #[cfg(feature = "rust-crypto")]
+    bash_prg_aead_crypto_wipe!(BashPrgAEADCipher5121);
+    #[cfg(feature = "rust-crypto")]
+    bash_prg_aead_crypto_wipe!(BashPrgAEADCipher5122);
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -757,7 +1295,76 @@ mod test {
         assert_eq!(hash, unsafe { *(l_.as_ptr() as *const [u8; 48]) });
     }
 
+    /// A.5 (l,d) = (128,2), m = 150, data and output in parts.
+    #[test]
+    fn hash_test_128_2_150_xof() {
+        let l_128_2_150 = [
+            0x48DB61832CA10090u64.to_be(),
+            0x03BC0D8BDE67893Au64.to_be(),
+            0x9DC683C48A5BC23Au64.to_be(),
+            0xC884EB4613B480A6u64.to_be(),
+        ];
+        let mut hash: [u8; 32] = [0; 32];
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+
+        let mut hasher = BashPrgHash2562::new([]).unwrap();
+        hasher.update(&s[..1]);
+        hasher.update(&s[1..100]);
+        hasher.update(&s[100..150]);
+        let mut reader = hasher.finalize_xof();
+        reader.read(&mut hash[..5]);
+        reader.read(&mut hash[5..]);
+
+        assert_eq!(hash, unsafe { *(l_128_2_150.as_ptr() as *const [u8; 32]) });
+
+        // Longer output continues the shorter one.
+        let mut long_hash: [u8; 300] = [0; 300];
+        let mut reader = BashPrgHash2562::new([]).unwrap().finalize_xof();
+        reader.read(&mut long_hash[..]);
+        let mut hasher = BashPrgHash2562::new([]).unwrap();
+        hasher.hash([], &mut hash);
+        assert_eq!(hash[..], long_hash[..32]);
+    }
+
+    #[cfg(feature = "rust-crypto")]
+    #[test]
+    fn hash_test_xof_digest() {
+        use digest::{ExtendableOutput, Update, XofReader};
+
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let mut hasher = BashPrgHash3841::default();
+        Update::update(&mut hasher, &s[..43]);
+        Update::update(&mut hasher, &s[43..143]);
+        let mut reader = ExtendableOutput::finalize_xof(hasher);
+        let mut hash: [u8; 48] = [0; 48];
+        XofReader::read(&mut reader, &mut hash);
+
+        let mut expected: [u8; 48] = [0; 48];
+        BashPrgHash3841::new([])
+            .unwrap()
+            .hash(&s[..143], &mut expected);
+        assert_eq!(hash, expected);
+    }
+
     /// A.6 (l,d) = (256,1)
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_test_io() {
+        use std::io::{copy, Cursor, Read};
+
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let mut hasher = BashPrgHash5122::new([]).unwrap();
+        copy(&mut Cursor::new(&s[..150]), &mut hasher).unwrap();
+        let mut hash = [0u8; 64];
+        Read::read_exact(&mut hasher.finalize_xof(), &mut hash).unwrap();
+
+        let mut expected = [0u8; 64];
+        BashPrgHash5122::new([])
+            .unwrap()
+            .hash(&s[..150], &mut expected);
+        assert_eq!(hash, expected);
+    }
+
     #[test]
     fn aead_test() {
         let y_ = [
@@ -798,4 +1405,300 @@ mod test {
 
         assert_eq!(y, unsafe { *(y_.as_ptr() as *const [u8; 192]) });
     }
+
+    /// A.6 (l,d) = (256,1), in place.
+    #[test]
+    fn aead_test_in_place() {
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let x: [u8; 192] = [0; 192];
+        let mut y: [u8; 192] = [0; 192];
+        let mut t: [u8; 32] = [0; 32];
+        let mut aead = BashPrgAEAD5121::new(&s[0..16], &s[32..64]).unwrap();
+        aead.encrypt(x, &s[64..113], &mut y, &mut t).unwrap();
+
+        let mut buffer = x;
+        let mut tag: [u8; 32] = [0; 32];
+        let mut aead = BashPrgAEAD5121::new(&s[0..16], &s[32..64]).unwrap();
+        aead.encrypt_in_place_detached(&s[64..113], &mut buffer, &mut tag)
+            .unwrap();
+        assert_eq!(buffer, y);
+        assert_eq!(tag, t);
+
+        let mut aead = BashPrgAEAD5121::new(&s[0..16], &s[32..64]).unwrap();
+        aead.decrypt_in_place_detached(&s[64..113], &mut buffer, tag)
+            .unwrap();
+        assert_eq!(buffer, x);
+
+        // Tag of 40 octets is compared in two parts.
+        let mut buffer = s.to_vec();
+        let mut aead = BashPrgAEAD3842::new(&s[0..16], &s[32..64]).unwrap();
+        aead.encrypt_in_place(&s[64..113], &mut buffer, 40).unwrap();
+        assert_eq!(buffer.len(), 192 + 40);
+
+        let mut forged = buffer.clone();
+        forged[192 + 35] ^= 0x80;
+        let mut aead = BashPrgAEAD3842::new(&s[0..16], &s[32..64]).unwrap();
+        assert!(aead.decrypt_in_place(&s[64..113], &mut forged, 40).is_err());
+        assert!(forged.is_empty());
+
+        let mut aead = BashPrgAEAD3842::new(&s[0..16], &s[32..64]).unwrap();
+        aead.decrypt_in_place(&s[64..113], &mut buffer, 40).unwrap();
+        assert_eq!(buffer, &s[..]);
+
+        let mut aead = BashPrgAEAD3842::new(&s[0..16], &s[32..64]).unwrap();
+        assert!(aead.decrypt_in_place([], &mut vec![0; 39], 40).is_err());
+
+        // Empty and short tags are rejected on both sides, data is not processed.
+        let mut forged = s.to_vec();
+        let mut aead = BashPrgAEAD3842::new(&s[0..16], &s[32..64]).unwrap();
+        assert!(matches!(
+            aead.decrypt_in_place(&s[64..113], &mut forged, 0),
+            Err(Error::InvalidLength(_))
+        ));
+        assert!(forged.is_empty());
+        let mut aead = BashPrgAEAD5121::new(&s[0..16], &s[32..64]).unwrap();
+        let mut buffer = y;
+        assert!(matches!(
+            aead.decrypt_in_place_detached(&s[64..113], &mut buffer, []),
+            Err(Error::InvalidLength(_))
+        ));
+        assert!(matches!(
+            aead.decrypt_in_place_detached(&s[64..113], &mut buffer, &t[..31]),
+            Err(Error::InvalidLength(_))
+        ));
+        assert_eq!(buffer, y);
+        let mut buffer = x;
+        let mut tag = [0u8; 31];
+        assert!(matches!(
+            aead.encrypt_in_place_detached(&s[64..113], &mut buffer, &mut tag),
+            Err(Error::InvalidLength(_))
+        ));
+        assert_eq!(buffer, x);
+        let mut buffer = x.to_vec();
+        assert!(matches!(
+            aead.encrypt_in_place(&s[64..113], &mut buffer, 8),
+            Err(Error::InvalidLength(_))
+        ));
+        assert_eq!(buffer, &x[..]);
+
+        // The rejected calls leave the automaton unchanged.
+        let mut buffer = x;
+        let mut tag = [0u8; 32];
+        aead.encrypt_in_place_detached(&s[64..113], &mut buffer, &mut tag)
+            .unwrap();
+        assert_eq!(buffer, y);
+        assert_eq!(tag, t);
+
+        // Tag of exactly l / 8 octets round-trips at every level.
+        let mut buffer = s[..50].to_vec();
+        let mut aead = BashPrgAEAD2561::new(&s[0..16], &s[32..48]).unwrap();
+        aead.encrypt_in_place(&s[64..113], &mut buffer, 16).unwrap();
+        let mut aead = BashPrgAEAD2561::new(&s[0..16], &s[32..48]).unwrap();
+        assert!(matches!(
+            aead.decrypt_in_place(&s[64..113], &mut buffer.clone(), 15),
+            Err(Error::InvalidLength(_))
+        ));
+        aead.decrypt_in_place(&s[64..113], &mut buffer, 16).unwrap();
+        assert_eq!(buffer, &s[..50]);
+        let mut buffer = s[..50].to_vec();
+        let mut aead = BashPrgAEAD3841::new(&s[0..16], &s[32..56]).unwrap();
+        aead.encrypt_in_place(&s[64..113], &mut buffer, 24).unwrap();
+        let mut aead = BashPrgAEAD3841::new(&s[0..16], &s[32..56]).unwrap();
+        aead.decrypt_in_place(&s[64..113], &mut buffer, 24).unwrap();
+        assert_eq!(buffer, &s[..50]);
+    }
+
+    /// A.6 (l,d) = (256,1) through `aead` interface.
+    #[cfg(feature = "rust-crypto")]
+    #[test]
+    fn aead_test_rust_crypto() {
+        use aead::{Aead, KeyInit, Payload};
+
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let x: [u8; 192] = [0; 192];
+        let mut y: [u8; 192] = [0; 192];
+        let mut t: [u8; 32] = [0; 32];
+        let mut aead = BashPrgAEAD5121::new(&s[0..16], &s[32..64]).unwrap();
+        aead.encrypt(x, &s[64..113], &mut y, &mut t).unwrap();
+
+        let cipher = BashPrgAEADCipher5121::new_from_slice(&s[32..64]).unwrap();
+        let nonce = aead::Nonce::<BashPrgAEADCipher5121>::from_slice(&s[0..16]);
+        let payload = Payload {
+            msg: &x,
+            aad: &s[64..113],
+        };
+        let ct = cipher.encrypt(nonce, payload).unwrap();
+        assert_eq!(ct[..192], y[..]);
+        assert_eq!(ct[192..], t[..]);
+
+        let payload = Payload {
+            msg: &ct,
+            aad: &s[64..113],
+        };
+        assert_eq!(cipher.decrypt(nonce, payload).unwrap(), &x[..]);
+
+        let mut forged = ct.clone();
+        forged[10] ^= 1;
+        let payload = Payload {
+            msg: &forged,
+            aad: &s[64..113],
+        };
+        assert!(cipher.decrypt(nonce, payload).is_err());
+
+        let payload = Payload {
+            msg: &ct,
+            aad: &s[64..112],
+        };
+        assert!(cipher.decrypt(nonce, payload).is_err());
+    }
+
+    #[test]
+    fn prg_test_checkpoint() {
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+
+        let mut hasher = BashPrgHash3842::new(&s[..8]).unwrap();
+        hasher.update(&s[..100]);
+        let state = hasher.export_state();
+        let mut hasher = BashPrgHash3842::import_state(state).unwrap();
+        let mut hash = [0u8; 48];
+        hasher.hash(&s[100..], &mut hash);
+        let mut expected = [0u8; 48];
+        BashPrgHash3842::new(&s[..8])
+            .unwrap()
+            .hash(&s[..], &mut expected);
+        assert_eq!(hash, expected);
+
+        // Nothing loaded yet.
+        let state = BashPrgHash2561::new([]).unwrap().export_state();
+        let mut hasher = BashPrgHash2561::import_state(state).unwrap();
+        let mut hash = [0u8; 32];
+        hasher.hash([], &mut hash);
+        let mut expected = [0u8; 32];
+        BashPrgHash2561::new([]).unwrap().hash([], &mut expected);
+        assert_eq!(hash, expected);
+        assert_eq!(
+            BashPrgHash2562::import_state(state).err(),
+            Some(StateError::TypeMismatch)
+        );
+        assert_eq!(
+            BashPrg::import_state(state).err(),
+            Some(StateError::TypeMismatch)
+        );
+
+        // Automaton in key mode in the middle of encryption.
+        let mut prg = BashPrg::start(256, 1, &s[0..16], &s[32..64]).unwrap();
+        prg.absorb(&s[64..113]);
+        prg.encr_start().unwrap();
+        let mut y = [0u8; 192];
+        prg.encr_step(&mut y[..50]);
+        let mut resumed = BashPrg::import_state(prg.export_state()).unwrap();
+        prg.encr_step(&mut y[50..]);
+        prg.squeeze(&mut expected);
+        let mut y_ = [0u8; 192];
+        resumed.encr_step(&mut y_[50..]);
+        resumed.squeeze(&mut hash);
+        assert_eq!(y_[50..], y[50..]);
+        assert_eq!(hash, expected);
+        assert!(resumed.is_key_mode());
+    }
+
+    #[test]
+    fn mac_test() {
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+
+        // start(l, d, ann, key), absorb(data), squeeze(tag).
+        let mut expected = [0u8; 32];
+        let mut prg = BashPrg::start(256, 1, &s[0..16], &s[32..64]).unwrap();
+        prg.absorb(&s[64..113]);
+        prg.squeeze(&mut expected);
+
+        let mut tag = [0u8; 32];
+        let mut mac = BashPrgMac5121::new(&s[0..16], &s[32..64]).unwrap();
+        mac.mac(&s[64..113], &mut tag);
+        assert_eq!(tag, expected);
+        assert_eq!(BashPrgMac5121::output_size(), 32);
+
+        let mut mac = BashPrgMac5121::new(&s[0..16], &s[32..64]).unwrap();
+        mac.update(&s[64..80]);
+        mac.update(&s[80..100]);
+        assert!(mac.verify(&s[100..113], tag).is_ok());
+
+        // The next message is authenticated after ratchet.
+        prg.ratchet();
+        prg.absorb(&s[113..150]);
+        prg.squeeze(&mut expected);
+        assert!(mac.verify(&s[113..150], expected).is_ok());
+        assert!(mac.verify(&s[113..150], expected).is_err());
+
+        let mut mac = BashPrgMac5121::new(&s[0..16], &s[32..64]).unwrap();
+        let mut forged = tag;
+        forged[31] ^= 1;
+        assert!(mac.verify(&s[64..113], forged).is_err());
+        let mut mac = BashPrgMac5121::new(&s[0..16], &s[32..64]).unwrap();
+        assert!(mac.verify(&s[64..113], &tag[..31]).is_err());
+        let mut mac = BashPrgMac5121::new(&s[0..16], &s[32..64]).unwrap();
+        assert!(mac.verify(&s[64..113], []).is_err());
+
+        // Tag differs from hash-value and from tag under another key.
+        let mut hash = [0u8; 32];
+        BashPrgHash2562::new([])
+            .unwrap()
+            .hash(&s[64..113], &mut hash);
+        let mut mac = BashPrgMac2562::new([], &s[32..48]).unwrap();
+        mac.mac(&s[64..113], &mut tag);
+        assert_ne!(tag, hash);
+        let mut mac = BashPrgMac2562::new([], &s[48..64]).unwrap();
+        mac.mac(&s[64..113], &mut hash);
+        assert_ne!(tag, hash);
+
+        // Empty data.
+        let mut prg = BashPrg::start(192, 2, [], &s[32..56]).unwrap();
+        prg.absorb([]);
+        let mut expected = [0u8; 24];
+        prg.squeeze(&mut expected);
+        let mut tag = [0u8; 24];
+        BashPrgMac3842::new([], &s[32..56])
+            .unwrap()
+            .mac([], &mut tag);
+        assert_eq!(tag, expected);
+
+        assert!(BashPrgMac2561::new([], []).is_err());
+        assert!(BashPrgMac5122::new([], &s[32..48]).is_err());
+    }
+
+    #[cfg(feature = "rust-crypto")]
+    #[test]
+    fn mac_test_rust_crypto() {
+        use digest::{KeyInit, Mac};
+
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let mut tag = [0u8; 24];
+        let mut mac = <BashPrgMac3841 as PrgMac>::new([], &s[32..56]).unwrap();
+        PrgMac::mac(&mut mac, &s[64..113], &mut tag);
+
+        let mut mac = <BashPrgMac3841 as KeyInit>::new_from_slice(&s[32..56]).unwrap();
+        Mac::update(&mut mac, &s[64..113]);
+        assert_eq!(mac.clone().finalize().into_bytes()[..], tag[..]);
+        assert!(mac.clone().verify_slice(&tag).is_ok());
+        tag[0] ^= 1;
+        assert!(mac.verify_slice(&tag).is_err());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_test() {
+        use zeroize::Zeroize;
+
+        let s: [u8; 192] = unsafe { *(S.as_ptr() as *const [u8; 192]) };
+        let mut prg = BashPrg::start(256, 1, &s[0..16], &s[32..64]).unwrap();
+        prg.absorb(&s[64..113]);
+        prg.ratchet();
+        assert!(prg.state.t.iter().all(|&x| x == 0));
+        assert!(prg.state.s.iter().any(|&x| x != 0));
+
+        let mut aead = BashPrgAEAD5121::new(&s[0..16], &s[32..64]).unwrap();
+        aead.zeroize();
+        assert!(aead.prg.state.s.iter().all(|&x| x == 0));
+        assert_eq!(aead.prg.state.pos, 0);
+    }
 }
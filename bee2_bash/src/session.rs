@@ -0,0 +1,380 @@
+//! Secure channel session on `BashPrg`.
+//!
+//! Both parties share a key. Every direction of the channel has its own
+//! automaton `start(l, d, ann, key)`, `ann` is `ANN_INITIATOR` for messages of
+//! the initiator and `ANN_RESPONDER` for messages of the responder.
+//! Message number `seq` is processed as
+//!
+//! ```text
+//! absorb(<seq>_64), encr(msg), squeeze(tag), ratchet()
+//! ```
+//!
+//! so messages are bound to their order and the previous states of the
+//! automaton cannot be recovered from the current one. Sequence numbers are
+//! sent along with messages, messages are accepted only in the order they were
+//! sealed. A rejected message leaves the session unchanged.
+
+use bee2_core::error::SessionError;
+
+use crate::prg::{
+    BashPrg, PrgAbsorb, PrgDecr, PrgEncr, PrgRatchet, PrgRestart, PrgSqueeze, PrgStart,
+};
+
+/// Annotation of messages of the initiator.
+pub const ANN_INITIATOR: &[u8; 8] = b"session\x01";
+/// Annotation of messages of the responder.
+pub const ANN_RESPONDER: &[u8; 8] = b"session\x02";
+/// Annotation of `rekey`.
+pub const ANN_REKEY: &[u8; 8] = b"rekey\x00\x00\x00";
+
+/// Role of party in session.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    Initiator,
+    Responder,
+}
+
+/// Sealed message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sealed {
+    /// Sequence number.
+    pub seq: u64,
+    /// Ciphertext.
+    pub ct: Vec<u8>,
+    /// Authentication tag, l / 8 octets.
+    pub tag: Vec<u8>,
+}
+
+/// One side of a duplex channel.
+#[derive(Clone)]
+pub struct BashPrgSession {
+    /// Automaton of sent messages.
+    send: BashPrg,
+    /// Automaton of received messages.
+    recv: BashPrg,
+    /// Number of the next sent message.
+    send_seq: u64,
+    /// Number of the next received message.
+    recv_seq: u64,
+    /// Length of tag.
+    tag_len: usize,
+}
+
+impl BashPrgSession {
+    /// Session initializing.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l == 128 || l == 192 || l == 256
+    /// * d - capacity, d == 1 || d == 2
+    /// * key - shared key, key.len() % 4 == 0 && l / 8 <= key.len() <= 60
+    /// * role - role of this party, the other party takes another one.
+    pub fn new(
+        l: usize,
+        d: usize,
+        key: impl AsRef<[u8]>,
+        role: Role,
+    ) -> Result<Self, SessionError> {
+        let key = key.as_ref();
+        if key.is_empty() {
+            return Err(SessionError::InvalidParameters);
+        }
+        let initiator = BashPrg::start(l, d, ANN_INITIATOR, key)
+            .map_err(|_| SessionError::InvalidParameters)?;
+        let responder = BashPrg::start(l, d, ANN_RESPONDER, key)
+            .map_err(|_| SessionError::InvalidParameters)?;
+        let (send, recv) = match role {
+            Role::Initiator => (initiator, responder),
+            Role::Responder => (responder, initiator),
+        };
+        Ok(Self {
+            send,
+            recv,
+            send_seq: 0,
+            recv_seq: 0,
+            tag_len: l / 8,
+        })
+    }
+
+    /// Get length of tag.
+    pub fn tag_len(&self) -> usize {
+        self.tag_len
+    }
+
+    /// Get number of the next sent message.
+    pub fn send_seq(&self) -> u64 {
+        self.send_seq
+    }
+
+    /// Get number of the next received message.
+    pub fn recv_seq(&self) -> u64 {
+        self.recv_seq
+    }
+
+    /// Encrypt and authenticate message in place.
+    ///
+    /// # Arguments
+    ///
+    /// * buffer - message, replaced by ciphertext.
+    /// * tag - to store authentication tag, tag.len() == l / 8
+    ///
+    /// Returns sequence number of the message.
+    pub fn seal_in_place(
+        &mut self,
+        buffer: &mut [u8],
+        tag: &mut [u8],
+    ) -> Result<u64, SessionError> {
+        if tag.len() != self.tag_len {
+            return Err(SessionError::InvalidTagLength);
+        }
+        let seq = self.send_seq;
+        let next = seq.checked_add(1).ok_or(SessionError::Exhausted)?;
+
+        self.send.absorb(seq.to_le_bytes());
+        self.send.encr(buffer).expect("automaton is keyed");
+        self.send.squeeze(tag);
+        self.send.ratchet();
+        self.send_seq = next;
+
+        Ok(seq)
+    }
+
+    /// Check and decrypt message in place.
+    ///
+    /// # Arguments
+    ///
+    /// * seq - sequence number of the message.
+    /// * buffer - ciphertext, replaced by message or zeroed if tag is incorrect.
+    /// * tag - authentication tag, tag.len() == l / 8
+    ///
+    /// The message is written to `buffer` before the tag is checked. If the tag
+    /// is incorrect, `buffer` is zeroed and must not be used.
+    pub fn open_in_place(
+        &mut self,
+        seq: u64,
+        buffer: &mut [u8],
+        tag: impl AsRef<[u8]>,
+    ) -> Result<(), SessionError> {
+        let tag = tag.as_ref();
+        if tag.len() != self.tag_len {
+            return Err(SessionError::InvalidTagLength);
+        }
+        if seq != self.recv_seq {
+            return Err(SessionError::OutOfOrder {
+                expected: self.recv_seq,
+                actual: seq,
+            });
+        }
+        let next = seq.checked_add(1).ok_or(SessionError::Exhausted)?;
+
+        // The state is changed only by an authentic message.
+        #[cfg_attr(not(feature = "zeroize"), allow(clippy::clone_on_copy))]
+        let mut prg = self.recv.clone();
+        prg.absorb(seq.to_le_bytes());
+        prg.decr(buffer).expect("automaton is keyed");
+        if !crate::prg::squeeze_eq(&mut prg, tag) {
+            buffer.iter_mut().for_each(|x| *x = 0);
+            return Err(SessionError::IncorrectTag);
+        }
+        prg.ratchet();
+        self.recv = prg;
+        self.recv_seq = next;
+
+        Ok(())
+    }
+
+    /// Encrypt and authenticate message.
+    ///
+    /// # Arguments
+    ///
+    /// * msg - message.
+    pub fn seal(&mut self, msg: impl AsRef<[u8]>) -> Result<Sealed, SessionError> {
+        let mut ct = msg.as_ref().to_vec();
+        let mut tag = vec![0u8; self.tag_len];
+        let seq = self.seal_in_place(&mut ct, &mut tag)?;
+        Ok(Sealed { seq, ct, tag })
+    }
+
+    /// Check and decrypt message.
+    ///
+    /// # Arguments
+    ///
+    /// * sealed - message sealed by the other party.
+    ///
+    /// The message is decrypted into a new buffer before the tag is checked,
+    /// the buffer is zeroed and dropped if the tag is incorrect.
+    pub fn open(&mut self, sealed: &Sealed) -> Result<Vec<u8>, SessionError> {
+        let mut msg = sealed.ct.clone();
+        self.open_in_place(sealed.seq, &mut msg, &sealed.tag)?;
+        Ok(msg)
+    }
+
+    /// Change keys of both directions.
+    ///
+    /// A new key is squeezed from every automaton and loaded by `restart`.
+    /// Both parties call `rekey` at the same point: after the same number of
+    /// sent and received messages.
+    pub fn rekey(&mut self) {
+        let tag_len = self.tag_len;
+        for prg in [&mut self.send, &mut self.recv].iter_mut() {
+            let mut key = [0u8; 32];
+            let key = &mut key[..tag_len];
+            prg.squeeze(key);
+            prg.restart(ANN_REKEY, &*key).expect("key length is l / 8");
+            #[cfg(feature = "zeroize")]
+            zeroize::Zeroize::zeroize(key);
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+mod wipe {
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+
+    use super::*;
+
+    impl Zeroize for BashPrgSession {
+        fn zeroize(&mut self) {
+            self.send.zeroize();
+            self.recv.zeroize();
+        }
+    }
+
+    impl ZeroizeOnDrop for BashPrgSession {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: [u8; 32] = [
+        0xB1, 0x94, 0xBA, 0xC8, 0x0A, 0x08, 0xF5, 0x3B, 0x36, 0x6D, 0x00, 0x8E, 0x58, 0x4A, 0x5D,
+        0xE4, 0x85, 0x04, 0xFA, 0x9D, 0x1B, 0xB6, 0xC7, 0xAC, 0x25, 0x2E, 0x72, 0xC2, 0x02, 0xFD,
+        0xCE, 0x0D,
+    ];
+
+    fn parties(l: usize, d: usize) -> (BashPrgSession, BashPrgSession) {
+        (
+            BashPrgSession::new(l, d, &KEY[..l / 8], Role::Initiator).unwrap(),
+            BashPrgSession::new(l, d, &KEY[..l / 8], Role::Responder).unwrap(),
+        )
+    }
+
+    #[test]
+    fn session_test() {
+        for &(l, d) in [(128, 1), (192, 2), (256, 1)].iter() {
+            let (mut alice, mut bob) = parties(l, d);
+            for i in 0..5usize {
+                let msg = vec![i as u8; 100 * i];
+                let sealed = alice.seal(&msg).unwrap();
+                assert_eq!(sealed.seq, i as u64);
+                assert_eq!(sealed.tag.len(), l / 8);
+                assert_eq!(bob.open(&sealed).unwrap(), msg);
+
+                let reply = bob.seal(b"ack").unwrap();
+                assert_ne!(reply.ct[..], b"ack"[..]);
+                assert_eq!(alice.open(&reply).unwrap(), b"ack");
+            }
+            assert_eq!(alice.send_seq(), 5);
+            assert_eq!(bob.recv_seq(), 5);
+        }
+
+        // Directions are separated: a message does not open on the sender side.
+        let (mut alice, _) = parties(256, 2);
+        let (mut other, _) = parties(256, 2);
+        let sealed = alice.seal(b"hello").unwrap();
+        assert_eq!(other.open(&sealed), Err(SessionError::IncorrectTag));
+
+        // Every message is encrypted on a new state.
+        let (mut alice, _) = parties(128, 2);
+        let first = alice.seal([0u8; 16]).unwrap();
+        let second = alice.seal([0u8; 16]).unwrap();
+        assert_ne!(first.ct, second.ct);
+    }
+
+    #[test]
+    fn session_test_order() {
+        let (mut alice, mut bob) = parties(256, 2);
+        let m0 = alice.seal(b"zero").unwrap();
+        let m1 = alice.seal(b"one").unwrap();
+        let m2 = alice.seal(b"two").unwrap();
+
+        assert_eq!(
+            bob.open(&m1),
+            Err(SessionError::OutOfOrder {
+                expected: 0,
+                actual: 1
+            })
+        );
+        assert_eq!(bob.open(&m0).unwrap(), b"zero");
+        assert_eq!(
+            bob.open(&m0),
+            Err(SessionError::OutOfOrder {
+                expected: 1,
+                actual: 0
+            })
+        );
+
+        // Sequence number is authenticated.
+        let mut forged = m2.clone();
+        forged.seq = 1;
+        assert_eq!(bob.open(&forged), Err(SessionError::IncorrectTag));
+
+        // Forged messages are rejected without changing the session.
+        let mut forged = m1.clone();
+        forged.ct[0] ^= 1;
+        assert_eq!(bob.open(&forged), Err(SessionError::IncorrectTag));
+        forged = m1.clone();
+        forged.tag.pop();
+        assert_eq!(bob.open(&forged), Err(SessionError::InvalidTagLength));
+        assert_eq!(bob.recv_seq(), 1);
+        assert_eq!(bob.open(&m1).unwrap(), b"one");
+        assert_eq!(bob.open(&m2).unwrap(), b"two");
+
+        let mut buffer = *b"data";
+        let mut tag = [0u8; 31];
+        assert_eq!(
+            alice.seal_in_place(&mut buffer, &mut tag),
+            Err(SessionError::InvalidTagLength)
+        );
+        assert_eq!(buffer, *b"data");
+
+        alice.send_seq = u64::MAX;
+        assert_eq!(alice.seal(b"last"), Err(SessionError::Exhausted));
+    }
+
+    #[test]
+    fn session_test_rekey() {
+        let (mut alice, mut bob) = parties(192, 1);
+        let m0 = alice.seal(b"before").unwrap();
+        assert_eq!(bob.open(&m0).unwrap(), b"before");
+
+        alice.rekey();
+        bob.rekey();
+        let m1 = alice.seal(b"after").unwrap();
+        assert_eq!(bob.open(&m1).unwrap(), b"after");
+        let r1 = bob.seal(b"reply").unwrap();
+        assert_eq!(alice.open(&r1).unwrap(), b"reply");
+
+        // Session without rekey does not open messages after rekey.
+        let (mut alice, mut bob) = parties(192, 1);
+        alice.rekey();
+        let m0 = alice.seal(b"message").unwrap();
+        assert_eq!(bob.open(&m0), Err(SessionError::IncorrectTag));
+        bob.rekey();
+        assert_eq!(bob.open(&m0).unwrap(), b"message");
+
+        assert_eq!(
+            BashPrgSession::new(256, 2, [], Role::Initiator).err(),
+            Some(SessionError::InvalidParameters)
+        );
+        assert_eq!(
+            BashPrgSession::new(256, 2, &KEY[..16], Role::Initiator).err(),
+            Some(SessionError::InvalidParameters)
+        );
+        assert_eq!(
+            BashPrgSession::new(160, 2, &KEY[..20], Role::Initiator).err(),
+            Some(SessionError::InvalidParameters)
+        );
+    }
+}
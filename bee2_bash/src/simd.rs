@@ -0,0 +1,305 @@
+//! `bash-f` on SSE2 and AVX2.
+//!
+//! The state is kept as three rows `s[0..8]`, `s[8..16]`, `s[16..24]`,
+//! so `bash-s` processes all eight columns at once, and the words are
+//! moved by the permutation `P` after every round:
+//!
+//! ```text
+//! row0 <- (s15, s10, s9, s12, s11, s14, s13, s8)
+//! row1 <- (s17, s16, s19, s18, s21, s20, s23, s22)
+//! row2 <- (s6, s3, s0, s5, s2, s7, s4, s1)
+//! ```
+//!
+//! `avx2_x4` processes four states at once: word `w` of state `k` is
+//! `s[4 * w + k]`, so a register holds the same word of all states and
+//! the permutation `P` only renames registers, as in `consts::bash_f0`.
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use crate::consts::{p0, p1, p2, p3, p4, p5, C};
+
+/// Rotation amounts of `bash-s` for columns 0..8, see `consts::bash_r`.
+const M1: [u32; 8] = [8, 56, 8, 56, 8, 56, 8, 56];
+const N1: [u32; 8] = [53, 51, 37, 3, 21, 19, 5, 35];
+const M2: [u32; 8] = [14, 34, 46, 2, 14, 34, 46, 2];
+const N2: [u32; 8] = [1, 7, 49, 23, 33, 39, 17, 55];
+
+pub(crate) mod sse2 {
+    use super::*;
+
+    /// Lanes (x[0], y[1]).
+    #[inline(always)]
+    unsafe fn lo_hi(x: __m128i, y: __m128i) -> __m128i {
+        _mm_castpd_si128(_mm_shuffle_pd::<0b10>(
+            _mm_castsi128_pd(x),
+            _mm_castsi128_pd(y),
+        ))
+    }
+
+    /// Lanes (x[1], y[0]).
+    #[inline(always)]
+    unsafe fn hi_lo(x: __m128i, y: __m128i) -> __m128i {
+        _mm_castpd_si128(_mm_shuffle_pd::<0b01>(
+            _mm_castsi128_pd(x),
+            _mm_castsi128_pd(y),
+        ))
+    }
+
+    /// Rotation of lanes by `r0` and `r1` bits, `0 < r0, r1 < 64`.
+    macro_rules! rotl {
+        ($x:expr, ($r0:literal, $r1:literal)) => {{
+            let x = $x;
+            let x0 = _mm_or_si128(_mm_slli_epi64::<$r0>(x), _mm_srli_epi64::<{ 64 - $r0 }>(x));
+            let x1 = _mm_or_si128(_mm_slli_epi64::<$r1>(x), _mm_srli_epi64::<{ 64 - $r1 }>(x));
+            lo_hi(x0, x1)
+        }};
+    }
+
+    /// `bash-s` on two columns with rotation amounts `m1, n1, m2, n2` of both.
+    macro_rules! bash_s {
+        ($w0:expr, $w1:expr, $w2:expr, $m1:tt, $n1:tt, $m2:tt, $n2:tt) => {{
+            let (w0, w1, w2) = (&mut $w0, &mut $w1, &mut $w2);
+            let mut t2 = rotl!(*w0, $m1);
+            *w0 = _mm_xor_si128(*w0, _mm_xor_si128(*w1, *w2));
+            let mut t1 = _mm_xor_si128(*w1, rotl!(*w0, $n1));
+            *w1 = _mm_xor_si128(t1, t2);
+            *w2 = _mm_xor_si128(*w2, _mm_xor_si128(rotl!(*w2, $m2), rotl!(t1, $n2)));
+            t1 = _mm_or_si128(*w0, *w2);
+            t2 = _mm_and_si128(*w0, *w1);
+            let t0 = _mm_or_si128(_mm_xor_si128(*w2, _mm_set1_epi32(-1)), *w1);
+            *w0 = _mm_xor_si128(*w0, t0);
+            *w1 = _mm_xor_si128(*w1, t1);
+            *w2 = _mm_xor_si128(*w2, t2);
+        }};
+    }
+
+    /// # Safety
+    ///
+    /// CPU supports SSE2.
+    #[target_feature(enable = "sse2")]
+    pub(crate) unsafe fn bash_f0(s: &mut [u64; 24]) {
+        let p = s.as_mut_ptr() as *mut __m128i;
+        let mut r0 = [_mm_setzero_si128(); 4];
+        let mut r1 = [_mm_setzero_si128(); 4];
+        let mut r2 = [_mm_setzero_si128(); 4];
+        for j in 0..4 {
+            r0[j] = _mm_loadu_si128(p.add(j));
+            r1[j] = _mm_loadu_si128(p.add(4 + j));
+            r2[j] = _mm_loadu_si128(p.add(8 + j));
+        }
+
+        for c in C.iter() {
+            bash_s!(r0[0], r1[0], r2[0], (8, 56), (53, 51), (14, 34), (1, 7));
+            bash_s!(r0[1], r1[1], r2[1], (8, 56), (37, 3), (46, 2), (49, 23));
+            bash_s!(r0[2], r1[2], r2[2], (8, 56), (21, 19), (14, 34), (33, 39));
+            bash_s!(r0[3], r1[3], r2[3], (8, 56), (5, 35), (46, 2), (17, 55));
+            let (a0, a1, a2) = (r0, r1, r2);
+            r0 = [
+                hi_lo(a1[3], a1[1]),
+                hi_lo(a1[0], a1[2]),
+                hi_lo(a1[1], a1[3]),
+                hi_lo(a1[2], a1[0]),
+            ];
+            for j in 0..4 {
+                r1[j] = _mm_shuffle_epi32::<0x4E>(a2[j]);
+            }
+            r2 = [
+                lo_hi(a0[3], a0[1]),
+                lo_hi(a0[0], a0[2]),
+                lo_hi(a0[1], a0[3]),
+                lo_hi(a0[2], a0[0]),
+            ];
+            r2[3] = _mm_xor_si128(r2[3], _mm_set_epi64x(*c as i64, 0));
+        }
+
+        for j in 0..4 {
+            _mm_storeu_si128(p.add(j), r0[j]);
+            _mm_storeu_si128(p.add(4 + j), r1[j]);
+            _mm_storeu_si128(p.add(8 + j), r2[j]);
+        }
+    }
+}
+
+pub(crate) mod avx2 {
+    use super::*;
+
+    /// Rotation amounts of columns `4 * h..4 * h + 4`.
+    #[inline(always)]
+    unsafe fn amounts(r: &[u32; 8], h: usize) -> __m256i {
+        let r = &r[4 * h..4 * h + 4];
+        _mm256_setr_epi64x(r[0] as i64, r[1] as i64, r[2] as i64, r[3] as i64)
+    }
+
+    #[inline(always)]
+    unsafe fn rotl(x: __m256i, r: __m256i) -> __m256i {
+        _mm256_or_si256(
+            _mm256_sllv_epi64(x, r),
+            _mm256_srlv_epi64(x, _mm256_sub_epi64(_mm256_set1_epi64x(64), r)),
+        )
+    }
+
+    /// `bash-s` on columns `4 * h..4 * h + 4`, `r` are rotation amounts `m1, n1, m2, n2`.
+    #[inline(always)]
+    unsafe fn bash_s(w0: &mut __m256i, w1: &mut __m256i, w2: &mut __m256i, r: &[__m256i; 4]) {
+        let mut t2 = rotl(*w0, r[0]);
+        *w0 = _mm256_xor_si256(*w0, _mm256_xor_si256(*w1, *w2));
+        let mut t1 = _mm256_xor_si256(*w1, rotl(*w0, r[1]));
+        *w1 = _mm256_xor_si256(t1, t2);
+        *w2 = _mm256_xor_si256(*w2, _mm256_xor_si256(rotl(*w2, r[2]), rotl(t1, r[3])));
+        t1 = _mm256_or_si256(*w0, *w2);
+        t2 = _mm256_and_si256(*w0, *w1);
+        let t0 = _mm256_or_si256(_mm256_xor_si256(*w2, _mm256_set1_epi32(-1)), *w1);
+        *w0 = _mm256_xor_si256(*w0, t0);
+        *w1 = _mm256_xor_si256(*w1, t1);
+        *w2 = _mm256_xor_si256(*w2, t2);
+    }
+
+    /// # Safety
+    ///
+    /// CPU supports AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(crate) unsafe fn bash_f0(s: &mut [u64; 24]) {
+        let p = s.as_mut_ptr() as *mut __m256i;
+        let mut r0 = [_mm256_loadu_si256(p), _mm256_loadu_si256(p.add(1))];
+        let mut r1 = [_mm256_loadu_si256(p.add(2)), _mm256_loadu_si256(p.add(3))];
+        let mut r2 = [_mm256_loadu_si256(p.add(4)), _mm256_loadu_si256(p.add(5))];
+        let r = [
+            [
+                amounts(&M1, 0),
+                amounts(&N1, 0),
+                amounts(&M2, 0),
+                amounts(&N2, 0),
+            ],
+            [
+                amounts(&M1, 1),
+                amounts(&N1, 1),
+                amounts(&M2, 1),
+                amounts(&N2, 1),
+            ],
+        ];
+
+        for c in C.iter() {
+            for h in 0..2 {
+                bash_s(&mut r0[h], &mut r1[h], &mut r2[h], &r[h]);
+            }
+            // (15, 10, 9, 12 | 11, 14, 13, 8) of row1 (8, 9, 10, 11 | 12, 13, 14, 15).
+            let a = _mm256_permute4x64_epi64::<0b00_01_10_11>(r1[0]);
+            let b = _mm256_permute4x64_epi64::<0b00_01_10_11>(r1[1]);
+            let next0 = [
+                _mm256_blend_epi32::<0b0011_1100>(b, a),
+                _mm256_blend_epi32::<0b0011_1100>(a, b),
+            ];
+            // (17, 16, 19, 18 | 21, 20, 23, 22) of row2.
+            let next1 = [
+                _mm256_permute4x64_epi64::<0b10_11_00_01>(r2[0]),
+                _mm256_permute4x64_epi64::<0b10_11_00_01>(r2[1]),
+            ];
+            // (6, 3, 0, 5 | 2, 7, 4, 1) of row0.
+            let a = _mm256_permute4x64_epi64::<0b01_00_11_10>(r0[0]);
+            let b = _mm256_permute4x64_epi64::<0b01_00_11_10>(r0[1]);
+            r2 = [
+                _mm256_blend_epi32::<0b0011_1100>(b, a),
+                _mm256_blend_epi32::<0b0011_1100>(a, b),
+            ];
+            r2[1] = _mm256_xor_si256(r2[1], _mm256_setr_epi64x(0, 0, 0, *c as i64));
+            r0 = next0;
+            r1 = next1;
+        }
+
+        _mm256_storeu_si256(p, r0[0]);
+        _mm256_storeu_si256(p.add(1), r0[1]);
+        _mm256_storeu_si256(p.add(2), r1[0]);
+        _mm256_storeu_si256(p.add(3), r1[1]);
+        _mm256_storeu_si256(p.add(4), r2[0]);
+        _mm256_storeu_si256(p.add(5), r2[1]);
+    }
+}
+
+pub(crate) mod avx2_x4 {
+    use super::*;
+
+    macro_rules! rotl {
+        ($x:expr, $r:literal) => {{
+            let x = $x;
+            _mm256_or_si256(
+                _mm256_slli_epi64::<$r>(x),
+                _mm256_srli_epi64::<{ 64 - $r }>(x),
+            )
+        }};
+    }
+
+    macro_rules! bash_s {
+        ($s:expr, $i0:expr, $i1:expr, $i2:expr, $m1:literal, $n1:literal, $m2:literal, $n2:literal) => {{
+            let (i0, i1, i2) = ($i0 as usize, $i1 as usize, $i2 as usize);
+            let (mut w0, mut w1, mut w2) = ($s[i0], $s[i1], $s[i2]);
+            let mut t2 = rotl!(w0, $m1);
+            w0 = _mm256_xor_si256(w0, _mm256_xor_si256(w1, w2));
+            let mut t1 = _mm256_xor_si256(w1, rotl!(w0, $n1));
+            w1 = _mm256_xor_si256(t1, t2);
+            w2 = _mm256_xor_si256(w2, _mm256_xor_si256(rotl!(w2, $m2), rotl!(t1, $n2)));
+            t1 = _mm256_or_si256(w0, w2);
+            t2 = _mm256_and_si256(w0, w1);
+            let t0 = _mm256_or_si256(_mm256_xor_si256(w2, _mm256_set1_epi32(-1)), w1);
+            $s[i0] = _mm256_xor_si256(w0, t0);
+            $s[i1] = _mm256_xor_si256(w1, t1);
+            $s[i2] = _mm256_xor_si256(w2, t2);
+        }};
+    }
+
+    macro_rules! bash_r {
+        ($s:expr, $p:expr, $p_next:expr, $i:expr) => {{
+            bash_s!($s, $p(0), $p(8), $p(16), 8, 53, 14, 1);
+            bash_s!($s, $p(1), $p(9), $p(17), 56, 51, 34, 7);
+            bash_s!($s, $p(2), $p(10), $p(18), 8, 37, 46, 49);
+            bash_s!($s, $p(3), $p(11), $p(19), 56, 3, 2, 23);
+            bash_s!($s, $p(4), $p(12), $p(20), 8, 21, 14, 33);
+            bash_s!($s, $p(5), $p(13), $p(21), 56, 19, 34, 39);
+            bash_s!($s, $p(6), $p(14), $p(22), 8, 5, 46, 17);
+            bash_s!($s, $p(7), $p(15), $p(23), 56, 35, 2, 55);
+            let w = $p_next(23) as usize;
+            $s[w] = _mm256_xor_si256($s[w], _mm256_set1_epi64x(C[$i - 1] as i64));
+        }};
+    }
+
+    /// # Safety
+    ///
+    /// CPU supports AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(crate) unsafe fn bash_f0(s: &mut [u64; 96]) {
+        let p = s.as_mut_ptr() as *mut __m256i;
+        let mut w = [_mm256_setzero_si256(); 24];
+        for (i, w) in w.iter_mut().enumerate() {
+            *w = _mm256_loadu_si256(p.add(i));
+        }
+        bash_r!(w, p0, p1, 1);
+        bash_r!(w, p1, p2, 2);
+        bash_r!(w, p2, p3, 3);
+        bash_r!(w, p3, p4, 4);
+        bash_r!(w, p4, p5, 5);
+        bash_r!(w, p5, p0, 6);
+        bash_r!(w, p0, p1, 7);
+        bash_r!(w, p1, p2, 8);
+        bash_r!(w, p2, p3, 9);
+        bash_r!(w, p3, p4, 10);
+        bash_r!(w, p4, p5, 11);
+        bash_r!(w, p5, p0, 12);
+        bash_r!(w, p0, p1, 13);
+        bash_r!(w, p1, p2, 14);
+        bash_r!(w, p2, p3, 15);
+        bash_r!(w, p3, p4, 16);
+        bash_r!(w, p4, p5, 17);
+        bash_r!(w, p5, p0, 18);
+        bash_r!(w, p0, p1, 19);
+        bash_r!(w, p1, p2, 20);
+        bash_r!(w, p2, p3, 21);
+        bash_r!(w, p3, p4, 22);
+        bash_r!(w, p4, p5, 23);
+        bash_r!(w, p5, p0, 24);
+        for (i, w) in w.iter().enumerate() {
+            _mm256_storeu_si256(p.add(i), *w);
+        }
+    }
+}
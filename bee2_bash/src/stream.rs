@@ -0,0 +1,227 @@
+//! Encryption and decryption of `std::io` streams by `BashPrg`.
+
+use std::io::{Read, Result, Write};
+
+use crate::prg::{BashPrg, InvalidCommand, PrgDecr, PrgEncr};
+
+/// Size of the buffer used by `BashPrgWriter`.
+const CHUNK_LEN: usize = 4096;
+
+#[derive(Copy, Clone)]
+enum Direction {
+    Encr,
+    Decr,
+}
+
+impl Direction {
+    fn start(self, prg: &mut BashPrg) -> core::result::Result<(), InvalidCommand> {
+        match self {
+            Direction::Encr => prg.encr_start(),
+            Direction::Decr => prg.decr_start(),
+        }
+    }
+
+    fn step(self, prg: &mut BashPrg, buf: &mut [u8]) {
+        match self {
+            Direction::Encr => prg.encr_step(buf),
+            Direction::Decr => prg.decr_step(buf),
+        }
+    }
+}
+
+/// Reader which encrypts or decrypts data read from `inner`.
+///
+/// Command `encr` (`decr`) is started on construction, every `read` is a step of it.
+/// After an error of `inner` the automaton is in the state after the data actually read.
+pub struct BashPrgReader<R> {
+    prg: BashPrg,
+    inner: R,
+    direction: Direction,
+}
+
+impl<R: Read> BashPrgReader<R> {
+    /// Encrypt data of `inner`.
+    ///
+    /// # Arguments
+    ///
+    /// * prg - automaton started with key.
+    /// * inner - source of plaintext.
+    pub fn encrypt(prg: BashPrg, inner: R) -> core::result::Result<Self, InvalidCommand> {
+        Self::new(prg, inner, Direction::Encr)
+    }
+
+    /// Decrypt data of `inner`.
+    ///
+    /// # Arguments
+    ///
+    /// * prg - automaton started with key.
+    /// * inner - source of ciphertext.
+    pub fn decrypt(prg: BashPrg, inner: R) -> core::result::Result<Self, InvalidCommand> {
+        Self::new(prg, inner, Direction::Decr)
+    }
+
+    fn new(
+        mut prg: BashPrg,
+        inner: R,
+        direction: Direction,
+    ) -> core::result::Result<Self, InvalidCommand> {
+        direction.start(&mut prg)?;
+        Ok(Self {
+            prg,
+            inner,
+            direction,
+        })
+    }
+
+    /// Get automaton, e.g. to squeeze a tag, and `inner`.
+    pub fn into_parts(self) -> (BashPrg, R) {
+        (self.prg, self.inner)
+    }
+}
+
+impl<R: Read> Read for BashPrgReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.direction.step(&mut self.prg, &mut buf[..count]);
+        Ok(count)
+    }
+}
+
+/// Writer which encrypts or decrypts data before writing it to `inner`.
+///
+/// Command `encr` (`decr`) is started on construction, every `write` is a step of it.
+/// After an error of `inner` the written data is lost and the stream must be dropped.
+pub struct BashPrgWriter<W> {
+    prg: BashPrg,
+    inner: W,
+    direction: Direction,
+}
+
+impl<W: Write> BashPrgWriter<W> {
+    /// Encrypt data written to `inner`.
+    ///
+    /// # Arguments
+    ///
+    /// * prg - automaton started with key.
+    /// * inner - destination of ciphertext.
+    pub fn encrypt(prg: BashPrg, inner: W) -> core::result::Result<Self, InvalidCommand> {
+        Self::new(prg, inner, Direction::Encr)
+    }
+
+    /// Decrypt data written to `inner`.
+    ///
+    /// # Arguments
+    ///
+    /// * prg - automaton started with key.
+    /// * inner - destination of plaintext.
+    pub fn decrypt(prg: BashPrg, inner: W) -> core::result::Result<Self, InvalidCommand> {
+        Self::new(prg, inner, Direction::Decr)
+    }
+
+    fn new(
+        mut prg: BashPrg,
+        inner: W,
+        direction: Direction,
+    ) -> core::result::Result<Self, InvalidCommand> {
+        direction.start(&mut prg)?;
+        Ok(Self {
+            prg,
+            inner,
+            direction,
+        })
+    }
+
+    /// Get automaton, e.g. to squeeze a tag, and `inner`.
+    pub fn into_parts(self) -> (BashPrg, W) {
+        (self.prg, self.inner)
+    }
+}
+
+impl<W: Write> Write for BashPrgWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut chunk = [0u8; CHUNK_LEN];
+        let count = buf.len().min(CHUNK_LEN);
+        let chunk = &mut chunk[..count];
+        chunk.copy_from_slice(&buf[..count]);
+        self.direction.step(&mut self.prg, chunk);
+        let result = self.inner.write_all(chunk);
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(chunk);
+        result.map(|_| count)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{copy, Cursor};
+
+    use super::*;
+    use crate::prg::{PrgAbsorb, PrgSqueeze, PrgStart};
+
+    fn start(key: &[u8]) -> BashPrg {
+        let mut prg = BashPrg::start(192, 2, [0x11; 8], key).unwrap();
+        prg.absorb(b"header");
+        prg
+    }
+
+    #[test]
+    fn stream_test_encrypt() {
+        let key = [0x3Cu8; 32];
+        let plaintext: Vec<u8> = (0..10000).map(|i| (i * 13) as u8).collect();
+
+        let mut ciphertext = plaintext.clone();
+        let mut tag = [0u8; 24];
+        let mut prg = start(&key);
+        prg.encr(&mut ciphertext).unwrap();
+        prg.squeeze(&mut tag);
+
+        // Writer gets data in chunks of different lengths.
+        let mut writer = BashPrgWriter::encrypt(start(&key), Vec::new()).unwrap();
+        for chunk in plaintext.chunks(777) {
+            writer.write_all(chunk).unwrap();
+        }
+        let (mut prg, written) = writer.into_parts();
+        let mut tag_ = [0u8; 24];
+        prg.squeeze(&mut tag_);
+        assert_eq!(written, ciphertext);
+        assert_eq!(tag_, tag);
+
+        let mut reader = BashPrgReader::encrypt(start(&key), Cursor::new(&plaintext)).unwrap();
+        let mut read = Vec::new();
+        copy(&mut reader, &mut read).unwrap();
+        let (mut prg, _) = reader.into_parts();
+        prg.squeeze(&mut tag_);
+        assert_eq!(read, ciphertext);
+        assert_eq!(tag_, tag);
+    }
+
+    #[test]
+    fn stream_test_decrypt() {
+        let key = [0x3Cu8; 32];
+        let plaintext: Vec<u8> = (0..10000).map(|i| (i * 13) as u8).collect();
+        let mut ciphertext = Vec::new();
+        let mut writer = BashPrgWriter::encrypt(start(&key), &mut ciphertext).unwrap();
+        writer.write_all(&plaintext).unwrap();
+
+        let mut reader = BashPrgReader::decrypt(start(&key), Cursor::new(&ciphertext)).unwrap();
+        let mut decrypted = Vec::new();
+        copy(&mut reader, &mut decrypted).unwrap();
+        assert_eq!(decrypted, plaintext);
+
+        let mut writer = BashPrgWriter::decrypt(start(&key), Vec::new()).unwrap();
+        copy(&mut Cursor::new(&ciphertext), &mut writer).unwrap();
+        assert_eq!(writer.into_parts().1, plaintext);
+    }
+
+    #[test]
+    fn stream_test_keyless() {
+        let prg = BashPrg::start(128, 1, [], []).unwrap();
+        assert!(BashPrgWriter::encrypt(prg, Vec::new()).is_err());
+        let prg = BashPrg::start(128, 1, [], []).unwrap();
+        assert!(BashPrgReader::decrypt(prg, Cursor::new([0u8; 4])).is_err());
+    }
+}
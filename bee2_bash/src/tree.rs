@@ -0,0 +1,185 @@
+//! Tree hashing of large data.
+//!
+//! Data is split into leaves of `LEAF_LEN` octets (one empty leaf for empty data),
+//! every leaf is hashed by `Bash256`. Hash-values of a level are combined
+//! pairwise from left to right by `bash-prg-hash` (`BashPrgHash2562`) with
+//! annotation `ANN_NODE`, the last odd node is moved to the next level as is.
+//! The only node of the top level and the length of data in octets as
+//! `<len>_64` are hashed by `bash-prg-hash` with annotation `ANN_ROOT`:
+//!
+//! ```text
+//! leaf_i = bash256(data[LEAF_LEN * i..LEAF_LEN * (i + 1)])
+//! node   = bash-prg-hash(ANN_NODE, left || right)
+//! root   = bash-prg-hash(ANN_ROOT, top || <len>_64)
+//! ```
+//!
+//! `hash()` is the sequential reference, `hash_par()` (feature `rayon`)
+//! hashes leaves and nodes of a level in parallel and gives the same hash-value.
+
+use crate::hash::{Bash256, Hasher};
+use crate::prg::{BashPrgHash2562, PrgHasher};
+
+/// Length of leaf.
+pub const LEAF_LEN: usize = 1 << 16;
+/// Annotation of interior nodes.
+pub const ANN_NODE: &[u8; 8] = b"treenode";
+/// Annotation of the root.
+pub const ANN_ROOT: &[u8; 8] = b"treeroot";
+
+fn leaf(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    Bash256::hash(&mut hash, data);
+    hash
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    let mut hasher = BashPrgHash2562::new(ANN_NODE).unwrap();
+    hasher.update(left);
+    hasher.hash(right, &mut hash);
+    hash
+}
+
+/// Combine pair of nodes or move the last odd one.
+fn pair(nodes: &[[u8; 32]]) -> [u8; 32] {
+    match nodes {
+        [left, right] => node(left, right),
+        [single] => *single,
+        _ => unreachable!("nodes are taken by pairs"),
+    }
+}
+
+fn root(top: &[u8; 32], len: usize, hash: &mut [u8; 32]) {
+    let mut hasher = BashPrgHash2562::new(ANN_ROOT).unwrap();
+    hasher.update(top);
+    hasher.hash((len as u64).to_le_bytes(), hash);
+}
+
+/// Tree hashing of `src`, sequential implementation.
+///
+/// # Arguments
+///
+/// * hash - to store hash-value.
+/// * src - data to hash.
+pub fn hash(hash: &mut [u8; 32], src: impl AsRef<[u8]>) {
+    let src = src.as_ref();
+    let mut nodes: Vec<[u8; 32]> = if src.is_empty() {
+        vec![leaf(src)]
+    } else {
+        src.chunks(LEAF_LEN).map(leaf).collect()
+    };
+    while nodes.len() > 1 {
+        nodes = nodes.chunks(2).map(pair).collect();
+    }
+    root(&nodes[0], src.len(), hash);
+}
+
+/// Tree hashing of `src` on the `rayon` thread pool.
+///
+/// The hash-value is the same as of `hash()`.
+///
+/// # Arguments
+///
+/// * hash - to store hash-value.
+/// * src - data to hash.
+#[cfg(feature = "rayon")]
+pub fn hash_par(hash: &mut [u8; 32], src: impl AsRef<[u8]>) {
+    use rayon::prelude::*;
+
+    let src = src.as_ref();
+    let mut nodes: Vec<[u8; 32]> = if src.is_empty() {
+        vec![leaf(src)]
+    } else {
+        src.par_chunks(LEAF_LEN).map(leaf).collect()
+    };
+    while nodes.len() > 1 {
+        nodes = nodes.par_chunks(2).map(pair).collect();
+    }
+    root(&nodes[0], src.len(), hash);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 + (i >> 8)) as u8).collect()
+    }
+
+    #[test]
+    fn tree_test_construction() {
+        let src = data(3 * LEAF_LEN + 5);
+        let leaves: Vec<[u8; 32]> = src.chunks(LEAF_LEN).map(leaf).collect();
+        assert_eq!(leaves.len(), 4);
+        // Levels are combined pairwise: (l0, l1), (l2, l3).
+        let top = node(&node(&leaves[0], &leaves[1]), &node(&leaves[2], &leaves[3]));
+        let mut expected = [0u8; 32];
+        let mut hasher = BashPrgHash2562::new(ANN_ROOT).unwrap();
+        hasher.update(top);
+        hasher.hash((src.len() as u64).to_le_bytes(), &mut expected);
+
+        let mut hash_ = [0u8; 32];
+        hash(&mut hash_, &src);
+        assert_eq!(hash_, expected);
+
+        // The odd leaf is moved up: ((l0, l1), l2).
+        let src = &src[..2 * LEAF_LEN + 1];
+        let top = node(&node(&leaves[0], &leaves[1]), &leaf(&src[2 * LEAF_LEN..]));
+        let mut hasher = BashPrgHash2562::new(ANN_ROOT).unwrap();
+        hasher.update(top);
+        hasher.hash((src.len() as u64).to_le_bytes(), &mut expected);
+        hash(&mut hash_, src);
+        assert_eq!(hash_, expected);
+    }
+
+    #[test]
+    fn tree_test_domains() {
+        let mut hash_ = [0u8; 32];
+        let mut bash = [0u8; 32];
+        for len in [0, 1, 100].iter() {
+            let src = data(*len);
+            hash(&mut hash_, &src);
+            Bash256::hash(&mut bash, &src);
+            assert_ne!(hash_, bash);
+        }
+
+        // Data equal to concatenation of leaf hashes does not give an interior node.
+        let src = data(2 * LEAF_LEN);
+        let leaves: Vec<u8> = src.chunks(LEAF_LEN).flat_map(leaf).collect();
+        let mut other = [0u8; 32];
+        hash(&mut hash_, &src);
+        hash(&mut other, &leaves);
+        assert_ne!(hash_, other);
+
+        // Length is bound: zero tail of the last leaf changes the hash-value.
+        let mut src = data(LEAF_LEN + 10);
+        hash(&mut hash_, &src);
+        src.push(0);
+        hash(&mut other, &src);
+        assert_ne!(hash_, other);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn tree_test_par() {
+        for len in [
+            0,
+            1,
+            LEAF_LEN - 1,
+            LEAF_LEN,
+            LEAF_LEN + 1,
+            2 * LEAF_LEN,
+            5 * LEAF_LEN + 7,
+            8 * LEAF_LEN,
+        ]
+        .iter()
+        {
+            let src = data(*len);
+            let mut hash_ = [0u8; 32];
+            let mut hash_par_ = [0u8; 32];
+            hash(&mut hash_, &src);
+            hash_par(&mut hash_par_, &src);
+            assert_eq!(hash_, hash_par_, "len = {}", len);
+        }
+    }
+}
@@ -0,0 +1,396 @@
+//! Typestate interface of the automaton of STB 34.101.77, section 8.
+//!
+//! `TypedBashPrg<S, M>` wraps `BashPrg` and tracks the started command in `S`
+//! and the key mode in `M`, so an invalid order of commands does not compile:
+//!
+//! ```compile_fail,E0599
+//! use bee2_bash::typed::TypedBashPrg;
+//!
+//! let mut prg = TypedBashPrg::start(256, 1, []).unwrap();
+//! let mut buf = [0u8; 32];
+//! // `encr_step` without `encr_start`.
+//! prg.encr_step(&mut buf);
+//! ```
+//!
+//! ```compile_fail,E0599
+//! use bee2_bash::typed::TypedBashPrg;
+//!
+//! // `encr` without key.
+//! let prg = TypedBashPrg::start(256, 1, []).unwrap();
+//! let prg = prg.encr_start();
+//! ```
+//!
+//! The same sequence of commands gives the same output as `BashPrg`:
+//!
+//! ```
+//! use bee2_bash::typed::TypedBashPrg;
+//!
+//! let key = [0x42u8; 32];
+//! let mut text = *b"plaintext";
+//! let mut tag = [0u8; 32];
+//!
+//! let mut prg = TypedBashPrg::start_keyed(256, 1, [], key).unwrap().absorb_start();
+//! prg.absorb_step(b"header");
+//! let mut prg = prg.encr_start();
+//! prg.encr_step(&mut text);
+//! let mut prg = prg.squeeze_start();
+//! prg.squeeze_step(&mut tag);
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::prg::{
+    BashPrg, InvalidCommand, InvalidLength, PrgAbsorb, PrgDecr, PrgEncr, PrgRatchet, PrgRestart,
+    PrgSqueeze, PrgStart,
+};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Command started in the automaton.
+pub trait State: sealed::Sealed {}
+
+/// Mode of the automaton: with or without key.
+pub trait Mode: sealed::Sealed {}
+
+/// No command is started: after `start`, `restart` or `ratchet`.
+pub enum Ready {}
+/// Command `absorb` is started.
+pub enum Absorbing {}
+/// Command `squeeze` is started.
+pub enum Squeezing {}
+/// Command `encr` is started.
+pub enum Encrypting {}
+/// Command `decr` is started.
+pub enum Decrypting {}
+
+/// Automaton is started without key, commands `encr` and `decr` are not available.
+pub enum Keyless {}
+/// Automaton is started with key.
+pub enum Keyed {}
+
+impl sealed::Sealed for Ready {}
+impl sealed::Sealed for Absorbing {}
+impl sealed::Sealed for Squeezing {}
+impl sealed::Sealed for Encrypting {}
+impl sealed::Sealed for Decrypting {}
+impl sealed::Sealed for Keyless {}
+impl sealed::Sealed for Keyed {}
+
+impl State for Ready {}
+impl State for Absorbing {}
+impl State for Squeezing {}
+impl State for Encrypting {}
+impl State for Decrypting {}
+impl Mode for Keyless {}
+impl Mode for Keyed {}
+
+/// `BashPrg` with the started command `S` and key mode `M` checked at compile time.
+///
+/// Commands which start a new command consume the automaton and return it in new state,
+/// steps of the started command borrow it.
+pub struct TypedBashPrg<S: State, M: Mode> {
+    prg: BashPrg,
+    marker: PhantomData<(S, M)>,
+}
+
+fn wrap<S: State, M: Mode>(prg: BashPrg) -> TypedBashPrg<S, M> {
+    TypedBashPrg {
+        prg,
+        marker: PhantomData,
+    }
+}
+
+impl<S: State, M: Mode> Clone for TypedBashPrg<S, M> {
+    fn clone(&self) -> Self {
+        #[cfg_attr(not(feature = "zeroize"), allow(clippy::clone_on_copy))]
+        let prg = self.prg.clone();
+        wrap(prg)
+    }
+}
+
+impl TypedBashPrg<Ready, Keyless> {
+    /// Automaton initializing without key.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l == 128 || l == 192 || l == 256
+    /// * d - capacity, d == 1 || d == 2
+    /// * ann - annotation, ann.len() % 4 == 0 && ann.len() <= 60
+    pub fn start(l: usize, d: usize, ann: impl AsRef<[u8]>) -> Result<Self, InvalidLength> {
+        Ok(wrap(BashPrg::start(l, d, ann, [])?))
+    }
+
+    /// Wrap automaton of the dynamic interface.
+    ///
+    /// The started command of `prg` is finished by the next command.
+    /// Use `TypedBashPrg::from_keyed()` to keep commands `encr` and `decr`.
+    pub fn from_prg(prg: BashPrg) -> Self {
+        wrap(prg)
+    }
+}
+
+impl TypedBashPrg<Ready, Keyed> {
+    /// Automaton initializing with key.
+    ///
+    /// # Arguments
+    ///
+    /// * l - security level, l == 128 || l == 192 || l == 256
+    /// * d - capacity, d == 1 || d == 2
+    /// * ann - annotation, ann.len() % 4 == 0 && ann.len() <= 60
+    /// * key - key, key.len() % 4 == 0 && key.len() <= 60, key.len() >= l / 8
+    pub fn start_keyed(
+        l: usize,
+        d: usize,
+        ann: impl AsRef<[u8]>,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Self, InvalidLength> {
+        if key.as_ref().is_empty() {
+            return Err(InvalidLength);
+        }
+        Ok(wrap(BashPrg::start(l, d, ann, key)?))
+    }
+
+    /// Wrap automaton of the dynamic interface started with key.
+    ///
+    /// Returns `InvalidCommand` if `prg` is started without key.
+    pub fn from_keyed(prg: BashPrg) -> Result<Self, InvalidCommand> {
+        if !prg.is_key_mode() {
+            return Err(InvalidCommand);
+        }
+        Ok(wrap(prg))
+    }
+}
+
+impl<S: State, M: Mode> TypedBashPrg<S, M> {
+    /// Unwrap automaton of the dynamic interface.
+    pub fn into_inner(self) -> BashPrg {
+        self.prg
+    }
+
+    /// Automaton re-initializing, the key mode is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * ann - annotation, ann.len() % 4 == 0 && ann.len() <= 60
+    /// * key - key, key.len() % 4 == 0 && key.len() <= 60, key.len() == 0 || key.len() >= l / 8
+    pub fn restart(
+        mut self,
+        ann: impl AsRef<[u8]>,
+        key: impl AsRef<[u8]>,
+    ) -> Result<TypedBashPrg<Ready, M>, InvalidLength> {
+        self.prg.restart(ann, key)?;
+        Ok(wrap(self.prg))
+    }
+
+    /// Initializing of data loading into automaton.
+    pub fn absorb_start(mut self) -> TypedBashPrg<Absorbing, M> {
+        self.prg.absorb_start();
+        wrap(self.prg)
+    }
+
+    /// Load data into automaton.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - data to load
+    pub fn absorb(self, buf: impl AsRef<[u8]>) -> TypedBashPrg<Absorbing, M> {
+        let mut prg = self.absorb_start();
+        prg.absorb_step(buf);
+        prg
+    }
+
+    /// Initializing of data unloading from automaton.
+    pub fn squeeze_start(mut self) -> TypedBashPrg<Squeezing, M> {
+        self.prg.squeeze_start();
+        wrap(self.prg)
+    }
+
+    /// Unload data from automaton.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - data to unload
+    pub fn squeeze(self, buf: &mut [u8]) -> TypedBashPrg<Squeezing, M> {
+        let mut prg = self.squeeze_start();
+        prg.squeeze_step(buf);
+        prg
+    }
+
+    /// Automaton changing.
+    /// The state of automaton changes so that it is difficult to determine the previous state.
+    pub fn ratchet(mut self) -> TypedBashPrg<Ready, M> {
+        self.prg.ratchet();
+        wrap(self.prg)
+    }
+}
+
+impl<S: State> TypedBashPrg<S, Keyed> {
+    /// Initializing of data encryption using automaton.
+    pub fn encr_start(mut self) -> TypedBashPrg<Encrypting, Keyed> {
+        if self.prg.encr_start().is_err() {
+            unreachable!("automaton is started with key");
+        }
+        wrap(self.prg)
+    }
+
+    /// Encryption using automaton.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - data to encrypt
+    pub fn encr(self, buf: &mut [u8]) -> TypedBashPrg<Encrypting, Keyed> {
+        let mut prg = self.encr_start();
+        prg.encr_step(buf);
+        prg
+    }
+
+    /// Initializing of data decryption using automaton.
+    pub fn decr_start(mut self) -> TypedBashPrg<Decrypting, Keyed> {
+        if self.prg.decr_start().is_err() {
+            unreachable!("automaton is started with key");
+        }
+        wrap(self.prg)
+    }
+
+    /// Decryption using automaton.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - data to decrypt
+    pub fn decr(self, buf: &mut [u8]) -> TypedBashPrg<Decrypting, Keyed> {
+        let mut prg = self.decr_start();
+        prg.decr_step(buf);
+        prg
+    }
+}
+
+impl<M: Mode> TypedBashPrg<Absorbing, M> {
+    /// Loading step into automaton.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - data to load
+    pub fn absorb_step(&mut self, buf: impl AsRef<[u8]>) {
+        self.prg.absorb_step(buf);
+    }
+}
+
+impl<M: Mode> TypedBashPrg<Squeezing, M> {
+    /// Unloading step from automaton.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - data to unload
+    pub fn squeeze_step(&mut self, buf: &mut [u8]) {
+        self.prg.squeeze_step(buf);
+    }
+}
+
+impl TypedBashPrg<Encrypting, Keyed> {
+    /// Encryption step using automaton.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - data to encrypt
+    pub fn encr_step(&mut self, buf: &mut [u8]) {
+        self.prg.encr_step(buf);
+    }
+}
+
+impl TypedBashPrg<Decrypting, Keyed> {
+    /// Decryption step using automaton.
+    ///
+    /// # Arguments
+    ///
+    /// * buf - data to decrypt
+    pub fn decr_step(&mut self, buf: &mut [u8]) {
+        self.prg.decr_step(buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prg::programming;
+
+    /// Block 8.11 Programming with typestate interface.
+    #[test]
+    fn typed_test_programming() {
+        let s: [u8; 192] = core::array::from_fn(|i| (i * 29 + 7) as u8);
+        let mut y1 = [0u8; 23];
+        let mut y2 = [0u8; 23];
+        let mut k1 = [0u8; 16];
+        y1.copy_from_slice(&s[160..183]);
+        y2.copy_from_slice(&s[160..183]);
+        let (mut y1_, mut y2_, mut k1_) = (y1, y2, k1);
+        programming(
+            &s[0..32],
+            &s[32..127],
+            &s[128..144],
+            &s[144..148],
+            &mut y1_,
+            &mut y2_,
+            &mut k1_,
+        )
+        .unwrap();
+
+        TypedBashPrg::start_keyed(256, 2, [], &s[0..32])
+            .unwrap()
+            .absorb(&s[32..127])
+            .ratchet()
+            .squeeze(&mut k1);
+        let beta = TypedBashPrg::start_keyed(128, 1, &s[128..144], k1).unwrap();
+        let gamma = beta.clone().restart(&s[144..148], []).unwrap();
+        beta.encr(&mut y1);
+        gamma.encr(&mut y2);
+
+        assert_eq!(k1, k1_);
+        assert_eq!(y1, y1_);
+        assert_eq!(y2, y2_);
+    }
+
+    #[test]
+    fn typed_test_aead() {
+        let key = [0x3Cu8; 32];
+        let header = [0x5Au8; 40];
+        let plaintext: [u8; 300] = core::array::from_fn(|i| i as u8);
+
+        let mut dynamic = BashPrg::start(256, 2, [], key).unwrap();
+        let mut text_ = plaintext;
+        let mut tag_ = [0u8; 64];
+        dynamic.absorb(header);
+        dynamic.encr(&mut text_).unwrap();
+        dynamic.squeeze(&mut tag_);
+
+        let mut text = plaintext;
+        let mut tag = [0u8; 64];
+        let mut prg = TypedBashPrg::start_keyed(256, 2, [], key)
+            .unwrap()
+            .absorb_start();
+        header.chunks(7).for_each(|chunk| prg.absorb_step(chunk));
+        let mut prg = prg.encr_start();
+        text.chunks_mut(100).for_each(|chunk| prg.encr_step(chunk));
+        let mut prg = prg.squeeze_start();
+        tag.chunks_mut(10).for_each(|chunk| prg.squeeze_step(chunk));
+        assert_eq!(text, text_);
+        assert_eq!(tag, tag_);
+
+        let prg = TypedBashPrg::from_keyed(BashPrg::start(256, 2, [], key).unwrap());
+        let mut prg = prg.unwrap().absorb(header).decr_start();
+        prg.decr_step(&mut text);
+        prg.squeeze(&mut tag);
+        assert_eq!(text, plaintext);
+        assert_eq!(tag, tag_);
+    }
+
+    #[test]
+    fn typed_test_mode() {
+        let key = [0x3Cu8; 16];
+        assert!(TypedBashPrg::start_keyed(128, 1, [], []).is_err());
+        assert!(TypedBashPrg::start_keyed(128, 1, [], &key[..12]).is_err());
+        assert!(TypedBashPrg::from_keyed(BashPrg::start(128, 1, [], []).unwrap()).is_err());
+        assert!(TypedBashPrg::from_keyed(BashPrg::start(128, 1, [], key).unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,46 @@
+//! Vectors of `aead` interface.
+//!
+//! Rows are (key, nonce, aad, plaintext, ciphertext || tag, pass). The first row
+//! of `bash_prg_aead_5121` is A.6 of STB 34.101.77, the others use its key, nonce
+//! and header octets at other levels and lengths; rows with pass 0 have a
+//! corrupted ciphertext or tag.
+#![cfg(feature = "rust-crypto")]
+
+use std::convert::TryInto;
+
+use aead::new_test;
+use bee2_bash::{
+    BashPrgAEADCipher2561, BashPrgAEADCipher2562, BashPrgAEADCipher3841, BashPrgAEADCipher3842,
+    BashPrgAEADCipher5121, BashPrgAEADCipher5122,
+};
+
+new_test!(
+    bash_prg_aead_2561,
+    "bash_prg_aead_2561",
+    BashPrgAEADCipher2561
+);
+new_test!(
+    bash_prg_aead_2562,
+    "bash_prg_aead_2562",
+    BashPrgAEADCipher2562
+);
+new_test!(
+    bash_prg_aead_3841,
+    "bash_prg_aead_3841",
+    BashPrgAEADCipher3841
+);
+new_test!(
+    bash_prg_aead_3842,
+    "bash_prg_aead_3842",
+    BashPrgAEADCipher3842
+);
+new_test!(
+    bash_prg_aead_5121,
+    "bash_prg_aead_5121",
+    BashPrgAEADCipher5121
+);
+new_test!(
+    bash_prg_aead_5122,
+    "bash_prg_aead_5122",
+    BashPrgAEADCipher5122
+);
@@ -0,0 +1,196 @@
+//! Base64 (RFC 4648, section 4) encoding and decoding into caller buffers.
+
+use crate::error::PemError;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Length of encoding of `len` octets, padding included.
+pub fn encoded_len(len: usize) -> usize {
+    len.div_ceil(3) * 4
+}
+
+/// Upper bound of length of data decoded from `len` symbols.
+pub fn decoded_len(len: usize) -> usize {
+    len / 4 * 3
+}
+
+#[inline]
+fn symbol(x: u8) -> u8 {
+    ALPHABET[(x & 0x3F) as usize]
+}
+
+#[inline]
+fn value(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Encode data.
+///
+/// # Arguments
+///
+/// * src - data to encode.
+/// * dst - to store result, dst.len() >= encoded_len(src.len()).
+///
+/// Returns length of encoding.
+pub fn encode(src: impl AsRef<[u8]>, dst: &mut [u8]) -> Result<usize, PemError> {
+    let src = src.as_ref();
+    let len = encoded_len(src.len());
+    if dst.len() < len {
+        return Err(PemError::BufferTooSmall);
+    }
+    for (chunk, out) in src.chunks(3).zip(dst.chunks_exact_mut(4)) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        out[0] = symbol(b[0] >> 2);
+        out[1] = symbol(b[0] << 4 | b[1] >> 4);
+        out[2] = symbol(b[1] << 2 | b[2] >> 6);
+        out[3] = symbol(b[2]);
+        if chunk.len() < 3 {
+            out[3] = b'=';
+        }
+        if chunk.len() < 2 {
+            out[2] = b'=';
+        }
+    }
+    Ok(len)
+}
+
+/// Decode data.
+///
+/// Decoding is strict: padding is required, unused bits must be zero
+/// and whitespace is not allowed.
+///
+/// # Arguments
+///
+/// * src - base64 symbols.
+/// * dst - to store result, dst.len() >= decoded length.
+///
+/// Returns length of decoded data.
+pub fn decode(src: impl AsRef<[u8]>, dst: &mut [u8]) -> Result<usize, PemError> {
+    decode_symbols(src.as_ref().iter().copied(), dst)
+}
+
+/// Decode symbols of `src` in groups of four.
+pub(crate) fn decode_symbols(
+    src: impl Iterator<Item = u8>,
+    dst: &mut [u8],
+) -> Result<usize, PemError> {
+    let mut quad = [0u8; 4];
+    let mut count = 0;
+    let mut pos = 0;
+    let mut padded = false;
+
+    for c in src {
+        if padded {
+            return Err(PemError::InvalidBase64);
+        }
+        quad[count] = c;
+        count += 1;
+        if count == 4 {
+            let len = decode_quad(&quad, &mut dst[pos..])?;
+            padded = len < 3;
+            pos += len;
+            count = 0;
+        }
+    }
+
+    if count != 0 {
+        return Err(PemError::InvalidBase64);
+    }
+    Ok(pos)
+}
+
+fn decode_quad(quad: &[u8; 4], dst: &mut [u8]) -> Result<usize, PemError> {
+    let v0 = value(quad[0]).ok_or(PemError::InvalidBase64)?;
+    let v1 = value(quad[1]).ok_or(PemError::InvalidBase64)?;
+    let (len, v2, v3) = match (quad[2], quad[3]) {
+        (b'=', b'=') if v1 & 0x0F == 0 => (1, 0, 0),
+        (c2, b'=') => match value(c2) {
+            Some(v2) if v2 & 0x03 == 0 => (2, v2, 0),
+            _ => return Err(PemError::InvalidBase64),
+        },
+        (c2, c3) => match (value(c2), value(c3)) {
+            (Some(v2), Some(v3)) => (3, v2, v3),
+            _ => return Err(PemError::InvalidBase64),
+        },
+    };
+    if dst.len() < len {
+        return Err(PemError::BufferTooSmall);
+    }
+    let b = [v0 << 2 | v1 >> 4, v1 << 4 | v2 >> 2, v2 << 6 | v3];
+    dst[..len].copy_from_slice(&b[..len]);
+    Ok(len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// RFC 4648, section 10.
+    const VECTORS: [(&[u8], &[u8]); 7] = [
+        (b"", b""),
+        (b"f", b"Zg=="),
+        (b"fo", b"Zm8="),
+        (b"foo", b"Zm9v"),
+        (b"foob", b"Zm9vYg=="),
+        (b"fooba", b"Zm9vYmE="),
+        (b"foobar", b"Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn base64_test_rfc4648() {
+        for (data, encoding) in VECTORS.iter() {
+            let mut buf = [0u8; 8];
+            let len = encode(data, &mut buf).unwrap();
+            assert_eq!(&buf[..len], *encoding);
+            assert_eq!(len, encoded_len(data.len()));
+
+            let mut buf = [0u8; 6];
+            let len = decode(encoding, &mut buf).unwrap();
+            assert_eq!(&buf[..len], *data);
+        }
+    }
+
+    #[test]
+    fn base64_test_all_octets() {
+        let mut data = [0u8; 256];
+        data.iter_mut().enumerate().for_each(|(i, x)| *x = i as u8);
+        let mut encoding = [0u8; 344];
+        let len = encode(data, &mut encoding).unwrap();
+        assert_eq!(len, 344);
+        let mut decoded = [0u8; 258];
+        assert_eq!(decode(&encoding[..len], &mut decoded).unwrap(), 256);
+        assert_eq!(decoded[..256], data[..]);
+    }
+
+    #[test]
+    fn base64_test_invalid() {
+        let mut buf = [0u8; 8];
+        // Not padded.
+        assert_eq!(decode(b"Zm8", &mut buf), Err(PemError::InvalidBase64));
+        // Non-zero unused bits.
+        assert_eq!(decode(b"Zh==", &mut buf), Err(PemError::InvalidBase64));
+        assert_eq!(decode(b"Zm9=", &mut buf), Err(PemError::InvalidBase64));
+        // Data after padding.
+        assert_eq!(decode(b"Zg==Zg==", &mut buf), Err(PemError::InvalidBase64));
+        assert_eq!(decode(b"Z=8=", &mut buf), Err(PemError::InvalidBase64));
+        // Invalid symbol.
+        assert_eq!(decode(b"Zm 9", &mut buf), Err(PemError::InvalidBase64));
+        // Small buffers.
+        assert_eq!(
+            decode(b"Zm9v", &mut buf[..2]),
+            Err(PemError::BufferTooSmall)
+        );
+        assert_eq!(encode(b"foo", &mut buf[..3]), Err(PemError::BufferTooSmall));
+    }
+}
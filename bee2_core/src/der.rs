@@ -0,0 +1,716 @@
+//! Minimal DER (X.690) encoder and decoder.
+//!
+//! Only the low-tag-number form is supported, which covers the universal
+//! types used by keys, certificates and signatures and context tags [0]..[30].
+//! The reader rejects every encoding which is valid BER but not DER.
+
+use crate::error::DerError;
+
+/// Tag of INTEGER.
+pub const INTEGER: u8 = 0x02;
+/// Tag of BIT STRING.
+pub const BIT_STRING: u8 = 0x03;
+/// Tag of OCTET STRING.
+pub const OCTET_STRING: u8 = 0x04;
+/// Tag of NULL.
+pub const NULL: u8 = 0x05;
+/// Tag of OBJECT IDENTIFIER.
+pub const OID: u8 = 0x06;
+/// Tag of SEQUENCE (constructed).
+pub const SEQUENCE: u8 = 0x30;
+
+/// Tag of context-specific constructed element `[n]`, n < 31.
+pub const fn context(n: u8) -> u8 {
+    0xA0 | (n & 0x1F)
+}
+
+/// Tag of context-specific primitive element `[n]`, n < 31.
+pub const fn context_primitive(n: u8) -> u8 {
+    0x80 | (n & 0x1F)
+}
+
+/// Length of DER encoding of `len` as element length.
+fn length_len(len: usize) -> usize {
+    if len < 0x80 {
+        1
+    } else {
+        1 + (core::mem::size_of::<usize>() - len.leading_zeros() as usize / 8)
+    }
+}
+
+/// Length of whole TLV with content of `len` octets.
+pub fn encoded_len(len: usize) -> usize {
+    1 + length_len(len) + len
+}
+
+/// Validated content of OBJECT IDENTIFIER.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Oid<'a>(&'a [u8]);
+
+impl<'a> Oid<'a> {
+    /// Wrap encoded content of OBJECT IDENTIFIER (without tag and length).
+    ///
+    /// # Arguments
+    ///
+    /// * bytes - base-128 arcs, every arc in the shortest form and fits into `u64`.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DerError> {
+        if bytes.is_empty() || bytes[bytes.len() - 1] & 0x80 != 0 {
+            return Err(DerError::InvalidOid);
+        }
+        let mut first = true;
+        let mut arc = 0u64;
+        for &b in bytes {
+            if first && b == 0x80 {
+                return Err(DerError::InvalidOid);
+            }
+            arc = arc.checked_mul(128).ok_or(DerError::OidOverflow)? | (b & 0x7F) as u64;
+            first = b & 0x80 == 0;
+            if first {
+                arc = 0;
+            }
+        }
+        Ok(Oid(bytes))
+    }
+
+    /// Encoded content.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Iterator over arcs. The first two arcs are split as in X.690 8.19.4.
+    pub fn arcs(&self) -> impl Iterator<Item = u64> + 'a {
+        let mut sub = SubIdentifiers(self.0);
+        let first = sub.next().unwrap_or(0);
+        let (a0, a1) = match first {
+            0..=39 => (0, first),
+            40..=79 => (1, first - 40),
+            _ => (2, first - 80),
+        };
+        core::iter::once(a0).chain(core::iter::once(a1)).chain(sub)
+    }
+}
+
+struct SubIdentifiers<'a>(&'a [u8]);
+
+impl<'a> Iterator for SubIdentifiers<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.0.is_empty() {
+            return None;
+        }
+        // Arcs fit into `u64`, checked by `Oid::new()`.
+        let mut value = 0u64;
+        let mut i = 0;
+        loop {
+            let b = self.0[i];
+            value = (value << 7) | (b & 0x7F) as u64;
+            i += 1;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        self.0 = &self.0[i..];
+        Some(value)
+    }
+}
+
+/// Content of BIT STRING.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BitString<'a> {
+    /// Count of unused bits in the last octet, 0..=7.
+    pub unused_bits: u8,
+    /// Octets of the string, unused bits are zero.
+    pub data: &'a [u8],
+}
+
+/// DER decoder over a byte slice.
+#[derive(Clone, Debug)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    /// Whether all elements are read.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Not yet read data.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Tag of the next element without reading it.
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Check that all elements are read.
+    pub fn finish(self) -> Result<(), DerError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(DerError::TrailingData)
+        }
+    }
+
+    /// Read next element of any type.
+    ///
+    /// Returns tag and content.
+    pub fn read_any(&mut self) -> Result<(u8, &'a [u8]), DerError> {
+        let (&tag, rest) = self.data.split_first().ok_or(DerError::UnexpectedEnd)?;
+        if tag & 0x1F == 0x1F {
+            return Err(DerError::UnsupportedTag);
+        }
+        let (&first, mut rest) = rest.split_first().ok_or(DerError::UnexpectedEnd)?;
+        let len = match first {
+            0x00..=0x7F => first as usize,
+            // Indefinite length and reserved value.
+            0x80 | 0xFF => return Err(DerError::NonCanonicalLength),
+            _ => {
+                let count = (first & 0x7F) as usize;
+                if rest.len() < count {
+                    return Err(DerError::UnexpectedEnd);
+                }
+                if rest[0] == 0 {
+                    return Err(DerError::NonCanonicalLength);
+                }
+                if count > core::mem::size_of::<usize>() {
+                    return Err(DerError::LengthOverflow);
+                }
+                let len = rest[..count]
+                    .iter()
+                    .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+                if len < 0x80 {
+                    return Err(DerError::NonCanonicalLength);
+                }
+                rest = &rest[count..];
+                len
+            }
+        };
+        if rest.len() < len {
+            return Err(DerError::UnexpectedEnd);
+        }
+        let (content, rest) = rest.split_at(len);
+        self.data = rest;
+        Ok((tag, content))
+    }
+
+    /// Read next element with `tag`.
+    ///
+    /// Returns content.
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8], DerError> {
+        let mut reader = self.clone();
+        let (actual, content) = reader.read_any()?;
+        if actual != tag {
+            return Err(DerError::UnexpectedTag {
+                expected: tag,
+                actual,
+            });
+        }
+        *self = reader;
+        Ok(content)
+    }
+
+    /// Read next element if it has `tag`.
+    pub fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, DerError> {
+        match self.peek_tag() {
+            Some(actual) if actual == tag => self.read(tag).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Read INTEGER.
+    ///
+    /// Returns content: big-endian two's complement in the shortest form.
+    pub fn read_integer(&mut self) -> Result<&'a [u8], DerError> {
+        let content = self.read(INTEGER)?;
+        match content {
+            [] => Err(DerError::NonCanonicalInteger),
+            [0x00, b, ..] if b & 0x80 == 0 => Err(DerError::NonCanonicalInteger),
+            [0xFF, b, ..] if b & 0x80 != 0 => Err(DerError::NonCanonicalInteger),
+            _ => Ok(content),
+        }
+    }
+
+    /// Read non-negative INTEGER.
+    ///
+    /// Returns big-endian magnitude without the sign octet.
+    pub fn read_unsigned(&mut self) -> Result<&'a [u8], DerError> {
+        let content = self.read_integer()?;
+        if content[0] & 0x80 != 0 {
+            return Err(DerError::IntegerOverflow);
+        }
+        match content {
+            [0x00, rest @ ..] if !rest.is_empty() => Ok(rest),
+            _ => Ok(content),
+        }
+    }
+
+    /// Read non-negative INTEGER which fits into `u64`.
+    pub fn read_u64(&mut self) -> Result<u64, DerError> {
+        let magnitude = self.read_unsigned()?;
+        if magnitude.len() > 8 {
+            return Err(DerError::IntegerOverflow);
+        }
+        Ok(magnitude.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    /// Read OCTET STRING.
+    pub fn read_octet_string(&mut self) -> Result<&'a [u8], DerError> {
+        self.read(OCTET_STRING)
+    }
+
+    /// Read BIT STRING.
+    pub fn read_bit_string(&mut self) -> Result<BitString<'a>, DerError> {
+        let content = self.read(BIT_STRING)?;
+        let (&unused_bits, data) = content.split_first().ok_or(DerError::InvalidBitString)?;
+        if unused_bits > 7 || (data.is_empty() && unused_bits != 0) {
+            return Err(DerError::InvalidBitString);
+        }
+        if let Some(&last) = data.last() {
+            if last & ((1u8 << unused_bits) - 1) != 0 {
+                return Err(DerError::InvalidBitString);
+            }
+        }
+        Ok(BitString { unused_bits, data })
+    }
+
+    /// Read NULL.
+    pub fn read_null(&mut self) -> Result<(), DerError> {
+        if !self.read(NULL)?.is_empty() {
+            return Err(DerError::InvalidNull);
+        }
+        Ok(())
+    }
+
+    /// Read OBJECT IDENTIFIER.
+    pub fn read_oid(&mut self) -> Result<Oid<'a>, DerError> {
+        Oid::new(self.read(OID)?)
+    }
+
+    /// Read SEQUENCE.
+    ///
+    /// Returns reader over elements of the sequence.
+    pub fn read_sequence(&mut self) -> Result<Reader<'a>, DerError> {
+        self.read(SEQUENCE).map(Reader::new)
+    }
+
+    /// Read explicitly tagged context-specific element `[n]`.
+    ///
+    /// Returns reader over the inner element.
+    pub fn read_context(&mut self, n: u8) -> Result<Reader<'a>, DerError> {
+        self.read(context(n)).map(Reader::new)
+    }
+}
+
+/// DER encoder into a byte slice.
+///
+/// Constructed elements are written in one pass: content is written first,
+/// then it is shifted to make room for tag and length.
+#[derive(Debug)]
+pub struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Writer { buf, pos: 0 }
+    }
+
+    /// Count of written octets.
+    pub fn len(&self) -> usize {
+        self.pos
+    }
+
+    /// Whether nothing is written.
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+
+    /// Finish writing.
+    ///
+    /// Returns written encoding.
+    pub fn finish(self) -> &'a [u8] {
+        let buf: &'a [u8] = self.buf;
+        &buf[..self.pos]
+    }
+
+    fn reserve(&mut self, len: usize) -> Result<&mut [u8], DerError> {
+        if self.buf.len() - self.pos < len {
+            return Err(DerError::BufferTooSmall);
+        }
+        let start = self.pos;
+        self.pos += len;
+        Ok(&mut self.buf[start..self.pos])
+    }
+
+    fn write_header(&mut self, tag: u8, len: usize) -> Result<(), DerError> {
+        let header = self.reserve(length_len(len) + 1)?;
+        header[0] = tag;
+        if len < 0x80 {
+            header[1] = len as u8;
+        } else {
+            let count = header.len() - 2;
+            header[1] = 0x80 | count as u8;
+            header[2..]
+                .copy_from_slice(&len.to_be_bytes()[core::mem::size_of::<usize>() - count..]);
+        }
+        Ok(())
+    }
+
+    /// Write element with `tag` and `content`.
+    pub fn write(&mut self, tag: u8, content: &[u8]) -> Result<(), DerError> {
+        if tag & 0x1F == 0x1F {
+            return Err(DerError::UnsupportedTag);
+        }
+        if self.buf.len() - self.pos < encoded_len(content.len()) {
+            return Err(DerError::BufferTooSmall);
+        }
+        self.write_header(tag, content.len())?;
+        self.reserve(content.len())?.copy_from_slice(content);
+        Ok(())
+    }
+
+    /// Write constructed element with `tag`, content is written by `f`.
+    pub fn write_constructed(
+        &mut self,
+        tag: u8,
+        f: impl FnOnce(&mut Writer<'_>) -> Result<(), DerError>,
+    ) -> Result<(), DerError> {
+        if tag & 0x1F == 0x1F {
+            return Err(DerError::UnsupportedTag);
+        }
+        let start = self.pos;
+        let len = {
+            let mut inner = Writer::new(&mut self.buf[start..]);
+            f(&mut inner)?;
+            inner.pos
+        };
+        let header_len = 1 + length_len(len);
+        if self.buf.len() - start - len < header_len {
+            return Err(DerError::BufferTooSmall);
+        }
+        self.buf.copy_within(start..start + len, start + header_len);
+        self.write_header(tag, len)?;
+        self.pos += len;
+        Ok(())
+    }
+
+    /// Write INTEGER.
+    ///
+    /// # Arguments
+    ///
+    /// * content - big-endian two's complement in the shortest form.
+    pub fn write_integer(&mut self, content: &[u8]) -> Result<(), DerError> {
+        match content {
+            [] => Err(DerError::NonCanonicalInteger),
+            [0x00, b, ..] if b & 0x80 == 0 => Err(DerError::NonCanonicalInteger),
+            [0xFF, b, ..] if b & 0x80 != 0 => Err(DerError::NonCanonicalInteger),
+            _ => self.write(INTEGER, content),
+        }
+    }
+
+    /// Write non-negative INTEGER.
+    ///
+    /// # Arguments
+    ///
+    /// * magnitude - big-endian value, leading zeros are allowed.
+    pub fn write_unsigned(&mut self, magnitude: &[u8]) -> Result<(), DerError> {
+        let skip = magnitude.iter().take_while(|&&b| b == 0).count();
+        let magnitude = &magnitude[skip..];
+        match magnitude.first() {
+            None => self.write(INTEGER, &[0]),
+            Some(&b) if b & 0x80 != 0 => {
+                let len = magnitude.len() + 1;
+                if self.buf.len() - self.pos < encoded_len(len) {
+                    return Err(DerError::BufferTooSmall);
+                }
+                self.write_header(INTEGER, len)?;
+                let content = self.reserve(len)?;
+                content[0] = 0;
+                content[1..].copy_from_slice(magnitude);
+                Ok(())
+            }
+            Some(_) => self.write(INTEGER, magnitude),
+        }
+    }
+
+    /// Write non-negative INTEGER.
+    pub fn write_u64(&mut self, value: u64) -> Result<(), DerError> {
+        self.write_unsigned(&value.to_be_bytes())
+    }
+
+    /// Write OCTET STRING.
+    pub fn write_octet_string(&mut self, data: &[u8]) -> Result<(), DerError> {
+        self.write(OCTET_STRING, data)
+    }
+
+    /// Write BIT STRING.
+    pub fn write_bit_string(&mut self, bits: BitString<'_>) -> Result<(), DerError> {
+        if bits.unused_bits > 7
+            || (bits.data.is_empty() && bits.unused_bits != 0)
+            || bits
+                .data
+                .last()
+                .map_or(0, |&b| b & ((1u8 << bits.unused_bits) - 1))
+                != 0
+        {
+            return Err(DerError::InvalidBitString);
+        }
+        let len = bits.data.len() + 1;
+        if self.buf.len() - self.pos < encoded_len(len) {
+            return Err(DerError::BufferTooSmall);
+        }
+        self.write_header(BIT_STRING, len)?;
+        let content = self.reserve(len)?;
+        content[0] = bits.unused_bits;
+        content[1..].copy_from_slice(bits.data);
+        Ok(())
+    }
+
+    /// Write NULL.
+    pub fn write_null(&mut self) -> Result<(), DerError> {
+        self.write(NULL, &[])
+    }
+
+    /// Write OBJECT IDENTIFIER.
+    pub fn write_oid(&mut self, oid: Oid<'_>) -> Result<(), DerError> {
+        self.write(OID, oid.as_bytes())
+    }
+
+    /// Write OBJECT IDENTIFIER given by arcs.
+    ///
+    /// # Arguments
+    ///
+    /// * arcs - at least two arcs, arcs[0] <= 2, arcs[1] < 40 if arcs[0] < 2.
+    pub fn write_oid_arcs(&mut self, arcs: &[u64]) -> Result<(), DerError> {
+        if arcs.len() < 2 || arcs[0] > 2 || (arcs[0] < 2 && arcs[1] >= 40) {
+            return Err(DerError::InvalidOid);
+        }
+        let first = (arcs[0] * 40)
+            .checked_add(arcs[1])
+            .ok_or(DerError::InvalidOid)?;
+        self.write_constructed(OID, |w| {
+            for &arc in core::iter::once(&first).chain(&arcs[2..]) {
+                let count = (64 - arc.leading_zeros() as usize).div_ceil(7);
+                let out = w.reserve(count.max(1))?;
+                let last = out.len() - 1;
+                for (i, b) in out.iter_mut().enumerate() {
+                    *b = (arc >> (7 * (last - i))) as u8 & 0x7F;
+                    if i != last {
+                        *b |= 0x80;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Write SEQUENCE, elements are written by `f`.
+    pub fn write_sequence(
+        &mut self,
+        f: impl FnOnce(&mut Writer<'_>) -> Result<(), DerError>,
+    ) -> Result<(), DerError> {
+        self.write_constructed(SEQUENCE, f)
+    }
+
+    /// Write explicitly tagged context-specific element `[n]`, inner element is written by `f`.
+    pub fn write_context(
+        &mut self,
+        n: u8,
+        f: impl FnOnce(&mut Writer<'_>) -> Result<(), DerError>,
+    ) -> Result<(), DerError> {
+        self.write_constructed(context(n), f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// AlgorithmIdentifier of bign-with-hbelt: 1.2.112.0.2.0.34.101.45.12, NULL.
+    const ALG_ID: [u8; 15] = [
+        0x30, 0x0D, 0x06, 0x09, 0x2A, 0x70, 0x00, 0x02, 0x00, 0x22, 0x65, 0x2D, 0x0C, 0x05, 0x00,
+    ];
+
+    #[test]
+    fn der_read_sequence() {
+        let mut reader = Reader::new(&ALG_ID);
+        let mut seq = reader.read_sequence().unwrap();
+        let oid = seq.read_oid().unwrap();
+        let mut arcs = [0u64; 10];
+        assert_eq!(oid.arcs().count(), 10);
+        arcs.iter_mut().zip(oid.arcs()).for_each(|(x, y)| *x = y);
+        assert_eq!(arcs, [1, 2, 112, 0, 2, 0, 34, 101, 45, 12]);
+        seq.read_null().unwrap();
+        seq.finish().unwrap();
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn der_write_sequence() {
+        let mut buf = [0u8; 32];
+        let mut writer = Writer::new(&mut buf);
+        writer
+            .write_sequence(|w| {
+                w.write_oid_arcs(&[1, 2, 112, 0, 2, 0, 34, 101, 45, 12])?;
+                w.write_null()
+            })
+            .unwrap();
+        assert_eq!(writer.finish(), &ALG_ID[..]);
+
+        let mut buf = [0u8; 14];
+        let mut writer = Writer::new(&mut buf);
+        assert_eq!(
+            writer.write_sequence(|w| {
+                w.write_oid_arcs(&[1, 2, 112, 0, 2, 0, 34, 101, 45, 12])?;
+                w.write_null()
+            }),
+            Err(DerError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn der_long_length() {
+        let data = [0xAB; 300];
+        let mut buf = [0u8; 310];
+        let mut writer = Writer::new(&mut buf);
+        writer
+            .write_context(3, |w| w.write_octet_string(&data))
+            .unwrap();
+        let encoded = writer.finish();
+        assert_eq!(encoded.len(), 4 + 4 + 300);
+        assert_eq!(
+            encoded[..8],
+            [0xA3, 0x82, 0x01, 0x30, 0x04, 0x82, 0x01, 0x2C]
+        );
+
+        let mut reader = Reader::new(encoded);
+        let mut inner = reader.read_context(3).unwrap();
+        assert_eq!(inner.read_octet_string().unwrap(), &data[..]);
+        inner.finish().unwrap();
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn der_non_canonical_length() {
+        // Long form for short length.
+        let mut reader = Reader::new(&[0x04, 0x81, 0x01, 0x00]);
+        assert_eq!(
+            reader.read_octet_string(),
+            Err(DerError::NonCanonicalLength)
+        );
+        // Leading zero in length.
+        let mut reader = Reader::new(&[0x04, 0x82, 0x00, 0x80]);
+        assert_eq!(
+            reader.read_octet_string(),
+            Err(DerError::NonCanonicalLength)
+        );
+        // Indefinite length.
+        let mut reader = Reader::new(&[0x30, 0x80, 0x00, 0x00]);
+        assert_eq!(
+            reader.read_sequence().err(),
+            Some(DerError::NonCanonicalLength)
+        );
+        // Truncated content.
+        let mut reader = Reader::new(&[0x04, 0x02, 0x00]);
+        assert_eq!(reader.read_octet_string(), Err(DerError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn der_integer() {
+        let mut buf = [0u8; 32];
+        let mut writer = Writer::new(&mut buf);
+        writer.write_u64(0).unwrap();
+        writer.write_u64(0x80).unwrap();
+        writer.write_unsigned(&[0x00, 0x00, 0x7F, 0x01]).unwrap();
+        writer.write_integer(&[0xFF, 0x7F]).unwrap();
+        assert_eq!(
+            writer.write_integer(&[0x00, 0x7F]),
+            Err(DerError::NonCanonicalInteger)
+        );
+        let encoded = writer.finish();
+        assert_eq!(
+            encoded,
+            &[
+                0x02, 0x01, 0x00, 0x02, 0x02, 0x00, 0x80, 0x02, 0x02, 0x7F, 0x01, 0x02, 0x02, 0xFF,
+                0x7F
+            ]
+        );
+
+        let mut reader = Reader::new(encoded);
+        assert_eq!(reader.read_u64().unwrap(), 0);
+        assert_eq!(reader.read_unsigned().unwrap(), &[0x80]);
+        assert_eq!(reader.read_u64().unwrap(), 0x7F01);
+        assert_eq!(reader.clone().read_integer().unwrap(), &[0xFF, 0x7F]);
+        assert_eq!(reader.read_u64(), Err(DerError::IntegerOverflow));
+
+        let mut reader = Reader::new(&[0x02, 0x02, 0x00, 0x01]);
+        assert_eq!(reader.read_integer(), Err(DerError::NonCanonicalInteger));
+        let mut reader = Reader::new(&[0x02, 0x00]);
+        assert_eq!(reader.read_integer(), Err(DerError::NonCanonicalInteger));
+    }
+
+    #[test]
+    fn der_bit_string() {
+        let mut reader = Reader::new(&[0x03, 0x03, 0x04, 0xAB, 0xC0]);
+        assert_eq!(
+            reader.read_bit_string().unwrap(),
+            BitString {
+                unused_bits: 4,
+                data: &[0xAB, 0xC0]
+            }
+        );
+        let mut reader = Reader::new(&[0x03, 0x02, 0x04, 0xC8]);
+        assert_eq!(reader.read_bit_string(), Err(DerError::InvalidBitString));
+        let mut reader = Reader::new(&[0x03, 0x01, 0x01]);
+        assert_eq!(reader.read_bit_string(), Err(DerError::InvalidBitString));
+    }
+
+    #[test]
+    fn der_errors() {
+        let mut reader = Reader::new(&[0x04, 0x00, 0x05, 0x00]);
+        assert_eq!(
+            reader.read_integer(),
+            Err(DerError::UnexpectedTag {
+                expected: INTEGER,
+                actual: OCTET_STRING
+            })
+        );
+        assert_eq!(reader.read_optional(NULL), Ok(None));
+        assert_eq!(reader.read_optional(OCTET_STRING), Ok(Some(&[][..])));
+        assert_eq!(reader.clone().finish(), Err(DerError::TrailingData));
+        reader.read_null().unwrap();
+        reader.finish().unwrap();
+
+        assert_eq!(Oid::new(&[0x2A, 0x80, 0x01]), Err(DerError::InvalidOid));
+        assert_eq!(Oid::new(&[0x2A, 0x81]), Err(DerError::InvalidOid));
+
+        // u64::MAX is the largest arc, 2^64 is rejected before `arcs()`.
+        let mut max = [0xFF; 11];
+        max[0] = 0x2A;
+        max[1] = 0x81;
+        max[10] = 0x7F;
+        let oid = Oid::new(&max).unwrap();
+        assert_eq!(oid.arcs().last(), Some(u64::MAX));
+        let mut over = [0x80; 11];
+        over[0] = 0x2A;
+        over[1] = 0x82;
+        over[10] = 0x00;
+        assert_eq!(Oid::new(&over), Err(DerError::OidOverflow));
+        let mut encoded = [0x06, 11].to_vec();
+        encoded.extend_from_slice(&over);
+        assert_eq!(Reader::new(&encoded).read_oid(), Err(DerError::OidOverflow));
+        assert_eq!(
+            Reader::new(&[0x1F, 0x01, 0x00]).read_any(),
+            Err(DerError::UnsupportedTag)
+        );
+    }
+}
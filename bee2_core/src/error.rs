@@ -39,12 +39,215 @@ impl core::fmt::Display for IncorrectTag {
 #[cfg(feature = "std")]
 impl std::error::Error for IncorrectTag {}
 
+/// DER encoding or decoding error.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DerError {
+    /// Input ended in the middle of an element.
+    UnexpectedEnd,
+    /// Element has another tag than expected.
+    UnexpectedTag { expected: u8, actual: u8 },
+    /// Tag in high-tag-number form, not supported.
+    UnsupportedTag,
+    /// Indefinite length or length not in the shortest form.
+    NonCanonicalLength,
+    /// Length does not fit into `usize`.
+    LengthOverflow,
+    /// Empty INTEGER or INTEGER with redundant leading octets.
+    NonCanonicalInteger,
+    /// INTEGER is negative or does not fit into the requested type.
+    IntegerOverflow,
+    /// BIT STRING with invalid count of unused bits or non-zero padding.
+    InvalidBitString,
+    /// NULL with non-empty content.
+    InvalidNull,
+    /// OBJECT IDENTIFIER with invalid encoding of arcs.
+    InvalidOid,
+    /// Arc of OBJECT IDENTIFIER does not fit into `u64`.
+    OidOverflow,
+    /// Data left after the last element.
+    TrailingData,
+    /// Output buffer is too small.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for DerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match *self {
+            DerError::UnexpectedEnd => f.write_str("DerError: unexpected end of data"),
+            DerError::UnexpectedTag { expected, actual } => write!(
+                f,
+                "DerError: unexpected tag {:#04x}, expected {:#04x}",
+                actual, expected
+            ),
+            DerError::UnsupportedTag => f.write_str("DerError: unsupported tag"),
+            DerError::NonCanonicalLength => f.write_str("DerError: non-canonical length"),
+            DerError::LengthOverflow => f.write_str("DerError: length overflow"),
+            DerError::NonCanonicalInteger => f.write_str("DerError: non-canonical integer"),
+            DerError::IntegerOverflow => f.write_str("DerError: integer overflow"),
+            DerError::InvalidBitString => f.write_str("DerError: invalid bit string"),
+            DerError::InvalidNull => f.write_str("DerError: invalid null"),
+            DerError::InvalidOid => f.write_str("DerError: invalid object identifier"),
+            DerError::OidOverflow => f.write_str("DerError: object identifier arc overflow"),
+            DerError::TrailingData => f.write_str("DerError: trailing data"),
+            DerError::BufferTooSmall => f.write_str("DerError: buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DerError {}
+
+/// PEM or base64 encoding or decoding error.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PemError {
+    /// Invalid symbol, padding or length of base64 data.
+    InvalidBase64,
+    /// No `-----BEGIN <label>-----` line.
+    MissingBegin,
+    /// No `-----END <label>-----` line.
+    MissingEnd,
+    /// Labels of BEGIN and END lines differ.
+    LabelMismatch,
+    /// Label is not one of the supported ones.
+    UnknownLabel,
+    /// Output buffer is too small.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for PemError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match *self {
+            PemError::InvalidBase64 => f.write_str("PemError: invalid base64"),
+            PemError::MissingBegin => f.write_str("PemError: missing BEGIN line"),
+            PemError::MissingEnd => f.write_str("PemError: missing END line"),
+            PemError::LabelMismatch => f.write_str("PemError: label mismatch"),
+            PemError::UnknownLabel => f.write_str("PemError: unknown label"),
+            PemError::BufferTooSmall => f.write_str("PemError: buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PemError {}
+
+/// Invalid parameters of key ladder.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LadderError {
+    /// Security level or capacity of automaton is not supported.
+    InvalidLevel,
+    /// Length of annotation is not a multiple of 4 or exceeds 60.
+    InvalidAnnotation,
+    /// Length of key does not match security level of the root.
+    InvalidKeyLength,
+    /// Length of child key does not match security level of the child.
+    InvalidChildKeyLength,
+    /// Ladder has no branches.
+    NoBranches,
+    /// Number of outputs differs from number of output branches.
+    BranchCountMismatch { expected: usize, actual: usize },
+    /// Number of child keys differs from number of child nodes.
+    ChildKeyCountMismatch { expected: usize, actual: usize },
+}
+
+impl core::fmt::Display for LadderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match *self {
+            LadderError::InvalidLevel => f.write_str("LadderError: invalid level"),
+            LadderError::InvalidAnnotation => f.write_str("LadderError: invalid annotation"),
+            LadderError::InvalidKeyLength => f.write_str("LadderError: invalid key length"),
+            LadderError::InvalidChildKeyLength => {
+                f.write_str("LadderError: invalid child key length")
+            }
+            LadderError::NoBranches => f.write_str("LadderError: no branches"),
+            LadderError::BranchCountMismatch { expected, actual } => {
+                write!(f, "LadderError: {} outputs, expected {}", actual, expected)
+            }
+            LadderError::ChildKeyCountMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "LadderError: {} child keys, expected {}",
+                    actual, expected
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LadderError {}
+
+/// Error of secure channel session.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SessionError {
+    /// Message has another sequence number than expected (reordered, replayed or lost).
+    OutOfOrder { expected: u64, actual: u64 },
+    /// Authentication tag is incorrect.
+    IncorrectTag,
+    /// Length of tag is not l / 8.
+    InvalidTagLength,
+    /// Sequence numbers are exhausted, the session must be restarted.
+    Exhausted,
+    /// Security level, capacity or length of key is not supported.
+    InvalidParameters,
+}
+
+impl core::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match *self {
+            SessionError::OutOfOrder { expected, actual } => {
+                write!(f, "SessionError: message {}, expected {}", actual, expected)
+            }
+            SessionError::IncorrectTag => f.write_str("SessionError: incorrect tag"),
+            SessionError::InvalidTagLength => f.write_str("SessionError: invalid tag length"),
+            SessionError::Exhausted => f.write_str("SessionError: sequence numbers exhausted"),
+            SessionError::InvalidParameters => f.write_str("SessionError: invalid parameters"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SessionError {}
+
+/// Error of import of exported state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StateError {
+    /// Data is not an exported state: wrong length, identifier or fields.
+    InvalidFormat,
+    /// Version of format is not supported.
+    UnsupportedVersion(u8),
+    /// Check value does not match data.
+    ChecksumMismatch,
+    /// State is exported by another type or with other parameters.
+    TypeMismatch,
+}
+
+impl core::fmt::Display for StateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match *self {
+            StateError::InvalidFormat => f.write_str("StateError: invalid format"),
+            StateError::UnsupportedVersion(version) => {
+                write!(f, "StateError: unsupported version {}", version)
+            }
+            StateError::ChecksumMismatch => f.write_str("StateError: checksum mismatch"),
+            StateError::TypeMismatch => f.write_str("StateError: type mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StateError {}
+
 /// General Error for bee2-rs
 #[derive(Clone, Debug)]
 pub enum Error {
     InvalidLength(InvalidLength),
     InvalidCommand(InvalidCommand),
     IncorrectTag(IncorrectTag),
+    DerError(DerError),
+    PemError(PemError),
+    LadderError(LadderError),
+    SessionError(SessionError),
+    StateError(StateError),
 }
 
 impl core::fmt::Display for Error {
@@ -53,6 +256,11 @@ impl core::fmt::Display for Error {
             Error::InvalidCommand(ref err) => write!(f, "{}", err),
             Error::InvalidLength(ref err) => write!(f, "{}", err),
             Error::IncorrectTag(ref err) => write!(f, "{}", err),
+            Error::DerError(ref err) => write!(f, "{}", err),
+            Error::PemError(ref err) => write!(f, "{}", err),
+            Error::LadderError(ref err) => write!(f, "{}", err),
+            Error::SessionError(ref err) => write!(f, "{}", err),
+            Error::StateError(ref err) => write!(f, "{}", err),
         }
     }
 }
@@ -75,5 +283,35 @@ impl From<IncorrectTag> for Error {
     }
 }
 
+impl From<DerError> for Error {
+    fn from(other: DerError) -> Self {
+        Error::DerError(other)
+    }
+}
+
+impl From<PemError> for Error {
+    fn from(other: PemError) -> Self {
+        Error::PemError(other)
+    }
+}
+
+impl From<LadderError> for Error {
+    fn from(other: LadderError) -> Self {
+        Error::LadderError(other)
+    }
+}
+
+impl From<SessionError> for Error {
+    fn from(other: SessionError) -> Self {
+        Error::SessionError(other)
+    }
+}
+
+impl From<StateError> for Error {
+    fn from(other: StateError) -> Self {
+        Error::StateError(other)
+    }
+}
+
 #[cfg(feature = "std")]
 impl std::error::Error for Error {}
@@ -1 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod base64;
+pub mod der;
 pub mod error;
+pub mod mem;
+pub mod pem;
@@ -0,0 +1,47 @@
+//! Memory operations for secret data.
+
+/// Compare `a` and `b` in time which depends only on their lengths.
+///
+/// Returns `false` if lengths differ.
+#[inline]
+pub fn mem_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    // The accumulator passes through `black_box` on every step, so the
+    // optimizer cannot see that it is saturated and leave the loop early.
+    let diff = a
+        .iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| core::hint::black_box(acc | (x ^ y)));
+    diff == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mem_eq_test() {
+        let a = [0x5Au8; 64];
+        let mut b = a;
+        assert!(mem_eq(&a, &b));
+        assert!(mem_eq(&[], &[]));
+
+        b[0] ^= 0x01;
+        assert!(!mem_eq(&a, &b));
+        b[0] ^= 0x01;
+        b[63] ^= 0x80;
+        assert!(!mem_eq(&a, &b));
+        b[63] ^= 0x80;
+        assert!(mem_eq(&a, &b));
+    }
+
+    #[test]
+    fn mem_eq_test_length() {
+        let a = [0x5Au8; 64];
+        assert!(!mem_eq(&a, &a[..63]));
+        assert!(!mem_eq(&a[..1], &a));
+        assert!(!mem_eq(&a[..0], &a[..1]));
+    }
+}
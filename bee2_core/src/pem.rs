@@ -0,0 +1,262 @@
+//! PEM (RFC 7468) armor for DER encodings.
+
+use crate::base64;
+use crate::error::PemError;
+
+/// Length of a line of base64 symbols.
+const LINE_LEN: usize = 64;
+
+/// Label of PEM encapsulation boundary.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Label {
+    /// X.509 certificate.
+    Certificate,
+    /// PKCS#8 private key.
+    PrivateKey,
+    /// PKCS#8 encrypted private key.
+    EncryptedPrivateKey,
+    /// SubjectPublicKeyInfo.
+    PublicKey,
+}
+
+impl Label {
+    /// Textual label as in RFC 7468.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Label::Certificate => "CERTIFICATE",
+            Label::PrivateKey => "PRIVATE KEY",
+            Label::EncryptedPrivateKey => "ENCRYPTED PRIVATE KEY",
+            Label::PublicKey => "PUBLIC KEY",
+        }
+    }
+
+    /// Label by its textual form.
+    pub fn from_bytes(label: impl AsRef<[u8]>) -> Option<Self> {
+        match label.as_ref() {
+            b"CERTIFICATE" => Some(Label::Certificate),
+            b"PRIVATE KEY" => Some(Label::PrivateKey),
+            b"ENCRYPTED PRIVATE KEY" => Some(Label::EncryptedPrivateKey),
+            b"PUBLIC KEY" => Some(Label::PublicKey),
+            _ => None,
+        }
+    }
+}
+
+const BEGIN: &[u8] = b"-----BEGIN ";
+const END: &[u8] = b"-----END ";
+const DASHES: &[u8] = b"-----";
+
+/// Length of PEM encoding of `len` octets of DER with `label`.
+pub fn encoded_len(label: Label, len: usize) -> usize {
+    let symbols = base64::encoded_len(len);
+    let boundaries = BEGIN.len() + END.len() + 2 * (label.as_str().len() + DASHES.len() + 1);
+    boundaries + symbols + symbols.div_ceil(LINE_LEN)
+}
+
+/// Encode DER.
+///
+/// Lines are 64 symbols long and end with `\n`.
+///
+/// # Arguments
+///
+/// * label - label of boundaries.
+/// * der - data to encode.
+/// * pem - to store result, pem.len() >= encoded_len(label, der.len()).
+///
+/// Returns length of encoding.
+pub fn encode(label: Label, der: impl AsRef<[u8]>, pem: &mut [u8]) -> Result<usize, PemError> {
+    let der = der.as_ref();
+    let len = encoded_len(label, der.len());
+    if pem.len() < len {
+        return Err(PemError::BufferTooSmall);
+    }
+
+    let mut pos = 0;
+    let mut put = |data: &[u8]| {
+        pem[pos..pos + data.len()].copy_from_slice(data);
+        pos += data.len();
+    };
+    put(BEGIN);
+    put(label.as_str().as_bytes());
+    put(DASHES);
+    put(b"\n");
+    // 48 octets give a line of 64 symbols.
+    for chunk in der.chunks(LINE_LEN / 4 * 3) {
+        let mut line = [0u8; LINE_LEN];
+        let count = base64::encode(chunk, &mut line)?;
+        put(&line[..count]);
+        put(b"\n");
+    }
+    put(END);
+    put(label.as_str().as_bytes());
+    put(DASHES);
+    put(b"\n");
+
+    Ok(len)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Read boundary line content after `prefix`: `<label>-----`.
+///
+/// Returns label and data after the line.
+fn boundary<'a>(data: &'a [u8], prefix: &[u8]) -> Option<(&'a [u8], &'a [u8])> {
+    let data = data.strip_prefix(prefix)?;
+    let end = find(data, DASHES)?;
+    let label = &data[..end];
+    if label.iter().any(|&c| c == b'\n' || c == b'\r') {
+        return None;
+    }
+    Some((label, &data[end + DASHES.len()..]))
+}
+
+/// Decode PEM.
+///
+/// Text before the BEGIN line and after the END line is ignored,
+/// line breaks and other whitespace inside base64 data are skipped.
+///
+/// # Arguments
+///
+/// * pem - PEM text.
+/// * der - to store result.
+///
+/// Returns label and length of DER.
+pub fn decode(pem: impl AsRef<[u8]>, der: &mut [u8]) -> Result<(Label, usize), PemError> {
+    let pem = pem.as_ref();
+    let start = find(pem, BEGIN).ok_or(PemError::MissingBegin)?;
+    let (label, body) = boundary(&pem[start..], BEGIN).ok_or(PemError::MissingBegin)?;
+    let end = find(body, END).ok_or(PemError::MissingEnd)?;
+    let (end_label, _) = boundary(&body[end..], END).ok_or(PemError::MissingEnd)?;
+    if label != end_label {
+        return Err(PemError::LabelMismatch);
+    }
+    let label = Label::from_bytes(label).ok_or(PemError::UnknownLabel)?;
+
+    let symbols = body[..end]
+        .iter()
+        .copied()
+        .filter(|c| !c.is_ascii_whitespace());
+    let len = base64::decode_symbols(symbols, der)?;
+
+    Ok((label, len))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// SubjectPublicKeyInfo of bign-pubkey, curve bign-curve256v1, public key 0x00..0x3F.
+    const SPKI: [u8; 95] = [
+        0x30, 0x5D, 0x30, 0x18, 0x06, 0x0A, 0x2A, 0x70, 0x00, 0x02, 0x00, 0x22, 0x65, 0x2D, 0x02,
+        0x01, 0x06, 0x0A, 0x2A, 0x70, 0x00, 0x02, 0x00, 0x22, 0x65, 0x2D, 0x03, 0x01, 0x03, 0x41,
+        0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+        0x0E, 0x0F, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C,
+        0x1D, 0x1E, 0x1F, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2A, 0x2B,
+        0x2C, 0x2D, 0x2E, 0x2F, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A,
+        0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
+    ];
+
+    const SPKI_PEM: &str = "-----BEGIN PUBLIC KEY-----
+MF0wGAYKKnAAAgAiZS0CAQYKKnAAAgAiZS0DAQNBAAABAgMEBQYHCAkKCwwNDg8Q
+ERITFBUWFxgZGhscHR4fICEiIyQlJicoKSorLC0uLzAxMjM0NTY3ODk6Ozw9Pj8=
+-----END PUBLIC KEY-----
+";
+
+    #[test]
+    fn pem_test_encode() {
+        let mut pem = [0u8; 256];
+        let len = encode(Label::PublicKey, SPKI, &mut pem).unwrap();
+        assert_eq!(len, encoded_len(Label::PublicKey, SPKI.len()));
+        assert_eq!(&pem[..len], SPKI_PEM.as_bytes());
+
+        assert_eq!(
+            encode(Label::PublicKey, SPKI, &mut pem[..len - 1]),
+            Err(PemError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn pem_test_decode() {
+        let mut der = [0u8; 96];
+        let (label, len) = decode(SPKI_PEM, &mut der).unwrap();
+        assert_eq!(label, Label::PublicKey);
+        assert_eq!(der[..len], SPKI[..]);
+
+        // Explanatory text and CRLF line breaks.
+        let mut text = [0u8; 256];
+        let mut pos = 0;
+        for &c in b"Subject: test\r\n".iter().chain(SPKI_PEM.as_bytes()) {
+            if c == b'\n' {
+                text[pos] = b'\r';
+                pos += 1;
+            }
+            text[pos] = c;
+            pos += 1;
+        }
+        let (label, len) = decode(&text[..pos], &mut der).unwrap();
+        assert_eq!(label, Label::PublicKey);
+        assert_eq!(der[..len], SPKI[..]);
+    }
+
+    #[test]
+    fn pem_test_round_trip() {
+        let labels = [
+            Label::Certificate,
+            Label::PrivateKey,
+            Label::EncryptedPrivateKey,
+            Label::PublicKey,
+        ];
+        let mut data = [0u8; 200];
+        data.iter_mut()
+            .enumerate()
+            .for_each(|(i, x)| *x = (i * 7) as u8);
+        for &label in labels.iter() {
+            for len in [0, 1, 47, 48, 49, 96, 200].iter() {
+                let mut pem = [0u8; 400];
+                let pem_len = encode(label, &data[..*len], &mut pem).unwrap();
+                let mut der = [0u8; 200];
+                assert_eq!(decode(&pem[..pem_len], &mut der).unwrap(), (label, *len));
+                assert_eq!(der[..*len], data[..*len]);
+            }
+        }
+    }
+
+    #[test]
+    fn pem_test_errors() {
+        let mut der = [0u8; 96];
+        assert_eq!(decode("MFswFQYJ", &mut der), Err(PemError::MissingBegin));
+        assert_eq!(
+            decode("-----BEGIN PUBLIC KEY-----\nMFswFQYJ\n", &mut der),
+            Err(PemError::MissingEnd)
+        );
+        assert_eq!(
+            decode(
+                "-----BEGIN PUBLIC KEY-----\nMFswFQYJ\n-----END CERTIFICATE-----\n",
+                &mut der
+            ),
+            Err(PemError::LabelMismatch)
+        );
+        assert_eq!(
+            decode(
+                "-----BEGIN RSA KEY-----\nMFswFQYJ\n-----END RSA KEY-----\n",
+                &mut der
+            ),
+            Err(PemError::UnknownLabel)
+        );
+        assert_eq!(
+            decode(
+                "-----BEGIN PUBLIC KEY-----\nMFswF*YJ\n-----END PUBLIC KEY-----\n",
+                &mut der
+            ),
+            Err(PemError::InvalidBase64)
+        );
+        assert_eq!(
+            decode(SPKI_PEM, &mut der[..94]),
+            Err(PemError::BufferTooSmall)
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use bee2_core::error::{Error, InvalidCommand, InvalidLength};
+use bee2_core::error::{Error, IncorrectTag, InvalidCommand, InvalidLength};
 
 /// The `Hasher` trait specifies an interface common for hasher functions as in original Bee2.
 /// Description of standart: <https://github.com/bcrypto/bash>
@@ -9,14 +9,13 @@ pub trait Hasher {
     /// Hash-value from state is recalculated with new data.
     fn step_h(&mut self, buf: impl AsRef<[u8]>);
 
-    // Getting of hash_value.
-    //
+    /// Getting of hash-value.
     fn step_g(&mut self, hash: &mut [u8]);
 
-    ///
+    /// Verification of hash-value.
     fn step_v(&mut self, hash: impl AsRef<[u8]>) -> bool;
 
-    ///
+    /// Hashing of `src` in one call.
     fn hash(hash: &mut [u8], src: impl AsRef<[u8]>);
 
     /// Get output size of the hasher
@@ -32,14 +31,14 @@ pub trait PrgHasher: Sized {
     /// * ann - annotation, ann.len() % 4 == 0 && ann.len() <= 60
     fn new(ann: impl AsRef<[u8]>) -> Result<Self, InvalidLength>;
 
-    /// Update hash with new data
-    /// 
+    /// Update hash with new data.
+    /// Consecutive calls load data as one message.
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * data - data to hash
     fn update(&mut self, data: impl AsRef<[u8]>);
 
-
     /// Calculate hash.
     ///
     /// # Arguments
@@ -49,6 +48,46 @@ pub trait PrgHasher: Sized {
     fn hash(&mut self, data: impl AsRef<[u8]>, hash: &mut [u8]);
 }
 
+/// The `PrgMac` trait specifies an interface common for all keyed hashers (MACs).
+pub trait PrgMac: Sized {
+    /// PrgMac initializing.
+    ///
+    /// # Arguments
+    ///
+    /// * ann - annotation, ann.len() % 4 == 0 && ann.len() <= 60
+    /// * key - key, key.len() % 4 == 0 && key.len() <= 60 && key.len() >= l / 8
+    fn new(ann: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> Result<Self, InvalidLength>;
+
+    /// Update tag with new data.
+    /// Consecutive calls load data as one message.
+    ///
+    /// # Arguments
+    ///
+    /// * data - data to authenticate
+    fn update(&mut self, data: impl AsRef<[u8]>);
+
+    /// Calculate tag.
+    /// Consecutive calls authenticate messages in a chain.
+    ///
+    /// # Arguments
+    ///
+    /// * data - data(message) to authenticate
+    /// * tag - output container.
+    fn mac(&mut self, data: impl AsRef<[u8]>, tag: &mut [u8]);
+
+    /// Verify tag in constant time.
+    ///
+    /// # Arguments
+    ///
+    /// * data - data(message) to authenticate
+    /// * tag - expected tag, tag.len() >= output_size()
+    fn verify(&mut self, data: impl AsRef<[u8]>, tag: impl AsRef<[u8]>)
+        -> Result<(), IncorrectTag>;
+
+    /// Get recommended size of tag.
+    fn output_size() -> usize;
+}
+
 /// The `PrgAEAD` trait specifies an interface common for all AEADs(authenticated encryption with associated data).
 pub trait PrgAEAD: Sized {
     /// PrgAE initializing.
@@ -66,15 +105,20 @@ pub trait PrgAEAD: Sized {
     /// * plaintext - data to encrypt.
     /// * header - associated data.
     /// * ciphertext - to store result, len(ciphertext) == len(plaintext).
-    /// * tag - authentication tag(message authentication code).
+    /// * tag - authentication tag(message authentication code), tag.len() >= l / 8
     ///
+    /// The default implementation copies `plaintext` to `ciphertext` and calls
+    /// `encrypt_in_place_detached()`.
     fn encrypt(
         &mut self,
         plaintext: impl AsRef<[u8]>,
         header: impl AsRef<[u8]>,
         ciphertext: &mut [u8],
         tag: &mut [u8],
-    ) -> Result<(), Error>;
+    ) -> Result<(), Error> {
+        ciphertext.copy_from_slice(plaintext.as_ref());
+        self.encrypt_in_place_detached(header, ciphertext, tag)
+    }
 
     /// Decrypt data.
     ///
@@ -82,16 +126,107 @@ pub trait PrgAEAD: Sized {
     ///
     /// * ciphertext - data to decrypt.
     /// * header - associated data.
-    /// * tag - authentication tag(message authentication code).
+    /// * tag - authentication tag(message authentication code), tag.len() >= l / 8
     /// * plaintext - to store result.
     ///
+    /// The default implementation copies `ciphertext` to `plaintext` and calls
+    /// `decrypt_in_place_detached()`.
     fn decrypt(
         &mut self,
         ciphertext: impl AsRef<[u8]>,
         header: impl AsRef<[u8]>,
         tag: impl AsRef<[u8]>,
         plaintext: &mut [u8],
+    ) -> Result<(), Error> {
+        plaintext.copy_from_slice(ciphertext.as_ref());
+        self.decrypt_in_place_detached(header, plaintext, tag)
+    }
+
+    /// Encrypt data in place.
+    ///
+    /// # Arguments
+    ///
+    /// * header - associated data.
+    /// * buffer - plaintext, replaced by ciphertext.
+    /// * tag - to store authentication tag, tag.len() >= l / 8
+    ///
+    /// A shorter tag is rejected with `InvalidLength` before any data is processed.
+    fn encrypt_in_place_detached(
+        &mut self,
+        header: impl AsRef<[u8]>,
+        buffer: &mut [u8],
+        tag: &mut [u8],
+    ) -> Result<(), Error>;
+
+    /// Decrypt data in place.
+    ///
+    /// # Arguments
+    ///
+    /// * header - associated data.
+    /// * buffer - ciphertext, replaced by plaintext or zeroed if tag is incorrect.
+    /// * tag - authentication tag, tag.len() >= l / 8
+    ///
+    /// A shorter tag is rejected with `InvalidLength` before any data is processed.
+    fn decrypt_in_place_detached(
+        &mut self,
+        header: impl AsRef<[u8]>,
+        buffer: &mut [u8],
+        tag: impl AsRef<[u8]>,
     ) -> Result<(), Error>;
+
+    /// Encrypt data in place and append authentication tag.
+    ///
+    /// # Arguments
+    ///
+    /// * header - associated data.
+    /// * buffer - plaintext, replaced by ciphertext || tag.
+    /// * tag_len - length of tag.
+    ///
+    fn encrypt_in_place(
+        &mut self,
+        header: impl AsRef<[u8]>,
+        buffer: &mut Vec<u8>,
+        tag_len: usize,
+    ) -> Result<(), Error> {
+        let len = buffer.len();
+        buffer.resize(len + tag_len, 0);
+        let (text, tag) = buffer.split_at_mut(len);
+        let result = self.encrypt_in_place_detached(header, text, tag);
+        if result.is_err() {
+            buffer.truncate(len);
+        }
+        result
+    }
+
+    /// Decrypt data with appended authentication tag in place.
+    ///
+    /// # Arguments
+    ///
+    /// * header - associated data.
+    /// * buffer - ciphertext || tag, replaced by plaintext, cleared on error.
+    /// * tag_len - length of tag.
+    ///
+    fn decrypt_in_place(
+        &mut self,
+        header: impl AsRef<[u8]>,
+        buffer: &mut Vec<u8>,
+        tag_len: usize,
+    ) -> Result<(), Error> {
+        if buffer.len() < tag_len {
+            buffer.clear();
+            return Err(Error::from(InvalidLength));
+        }
+        let len = buffer.len() - tag_len;
+        let (text, tag) = buffer.split_at_mut(len);
+        let result = self.decrypt_in_place_detached(header, text, &*tag);
+        if result.is_err() {
+            buffer.iter_mut().for_each(|x| *x = 0);
+            buffer.clear();
+        } else {
+            buffer.truncate(len);
+        }
+        result
+    }
 }
 
 /// The `PrgStart` trait specifies an interface for command `start`.